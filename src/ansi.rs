@@ -0,0 +1,203 @@
+use iced::Color;
+
+// Text between escape sequences, in the foreground color the SGR codes set for it;
+// None means the line's own color
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub text: String,
+    pub color: Option<Color>,
+}
+
+// xterm defaults: the eight standard colors, then their bright variants
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+fn palette(index: usize) -> Color {
+    let (r, g, b) = PALETTE[index];
+    Color::from_rgb8(r, g, b)
+}
+
+// 256-color index: 16 palette entries, a 6x6x6 cube, then 24 grays
+fn indexed(n: u16) -> Option<Color> {
+    match n {
+        0..=15 => Some(palette(n as usize)),
+        16..=231 => {
+            let n = n - 16;
+            let level = |v: u16| if v == 0 { 0 } else { (55 + v * 40) as u8 };
+            Some(Color::from_rgb8(
+                level(n / 36),
+                level(n / 6 % 6),
+                level(n % 6),
+            ))
+        }
+        232..=255 => {
+            let gray = (8 + (n - 232) * 10) as u8;
+            Some(Color::from_rgb8(gray, gray, gray))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct Style {
+    // Index into PALETTE before bold brightening, or an explicit color
+    base: Option<usize>,
+    explicit: Option<Color>,
+    bold: bool,
+}
+
+impl Style {
+    fn color(&self) -> Option<Color> {
+        if self.explicit.is_some() {
+            return self.explicit;
+        }
+        // Bold brightens the standard colors, as most terminals do
+        self.base
+            .map(|i| palette(if self.bold && i < 8 { i + 8 } else { i }))
+    }
+
+    fn apply(&mut self, params: &str) {
+        let codes: Vec<u16> = params
+            .split(';')
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                code @ 30..=37 => {
+                    self.base = Some((code - 30) as usize);
+                    self.explicit = None;
+                }
+                code @ 90..=97 => {
+                    self.base = Some((code - 90) as usize + 8);
+                    self.explicit = None;
+                }
+                39 => {
+                    self.base = None;
+                    self.explicit = None;
+                }
+                38 => match codes.get(i + 1) {
+                    Some(5) => {
+                        self.explicit = codes.get(i + 2).and_then(|&n| indexed(n));
+                        i += 2;
+                    }
+                    Some(2) => {
+                        if let [r, g, b] = codes[(i + 2).min(codes.len())..]
+                            .iter()
+                            .take(3)
+                            .map(|&v| v.min(255) as u8)
+                            .collect::<Vec<_>>()[..]
+                        {
+                            self.explicit = Some(Color::from_rgb8(r, g, b));
+                        }
+                        i += 4;
+                    }
+                    _ => {}
+                },
+                // Backgrounds, underline, blink and the rest are not shown
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+fn flush(text: &mut String, color: Option<Color>, segments: &mut Vec<Segment>) {
+    if !text.is_empty() {
+        segments.push(Segment {
+            text: std::mem::take(text),
+            color,
+        });
+    }
+}
+
+// Interprets SGR color codes and drops every other escape sequence (cursor movement,
+// erase, OSC titles) since the terminal view has no cursor to apply them to
+pub fn parse(input: &str) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut style = Style::default();
+    let mut current = String::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            current.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters, then a final byte in 0x40..=0x7E
+            Some('[') => {
+                let mut params = String::new();
+                let mut last = None;
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if last == Some('m') {
+                    flush(&mut current, style.color(), &mut segments);
+                    style.apply(&params);
+                }
+            }
+            // OSC: ends with BEL or ESC '\'
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // Two-character escapes such as ESC 7 / ESC 8
+            _ => {}
+        }
+    }
+    flush(&mut current, style.color(), &mut segments);
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_and_reset_split_segments() {
+        let segments = parse("ok \x1b[31merror\x1b[0m done");
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["ok ", "error", " done"]);
+        assert_eq!(segments[0].color, None);
+        assert_eq!(segments[1].color, Some(palette(1)));
+        assert_eq!(segments[2].color, None);
+    }
+
+    #[test]
+    fn cursor_sequences_are_dropped() {
+        let segments = parse("\x1b[2K\x1b[1A\x1b]0;title\x07prompt$ ");
+        assert_eq!(
+            segments,
+            vec![Segment {
+                text: "prompt$ ".to_string(),
+                color: None
+            }]
+        );
+    }
+}
@@ -1,53 +1,2772 @@
-use iced::{Element, Subscription, Task};
+use iced::keyboard::{self, key, Key, Modifiers};
+use iced::widget::{scrollable, svg, text_editor, text_input};
+use iced::{window, Color, Element, Font, Length, Size, Subscription, Task, Theme};
+use serialport::{
+    available_ports, ClearBuffer, DataBits, FlowControl, SerialPort, SerialPortInfo,
+    SerialPortType, StopBits,
+};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio_serial::SerialStream;
+use std::time::{Duration, Instant};
 
-pub use crate::ui::*; // re-export UI types if needed
+#[cfg(feature = "scripting")]
+use crate::byte_hook;
+use crate::config::*;
+use crate::file::*;
+use crate::hex;
+use crate::line::*;
+use crate::port_io::{PortEvent, PortIo, WriteJob, WriteKind};
+use crate::recording::TxRecording;
+use crate::serial::*;
+use crate::{line_diff, script, traffic_chart, zmodem};
 
-pub struct App {
-    // basic state
-    pub terminal: String,
-    pub input: String,
+mod automation;
+mod connection;
+mod logging;
+mod transfer;
+mod view;
 
-    // serial handle (optional)
-    pub port: Option<Arc<Mutex<SerialStream>>>,
+use automation::*;
+use connection::*;
+use logging::*;
+use transfer::*;
 
-    // UI settings (placeholders)
-    pub selected_port: Option<String>,
+#[derive(Debug, Clone)]
+pub enum Message {
+    // Навигация
+    ShowTerminal,
+    ShowSettings,
+    ShowMonitor,
+    ShowFileView,
+    ShowCompare,
+    ToggleLogWindow,
+    LoadCompareLog(usize),
+    CompareLogLoaded(usize, Result<Option<(String, Vec<u8>)>, String>),
+    WindowOpened(window::Id),
+    FontLoaded(Result<(), iced::font::Error>),
+    MonospaceToggled(bool),
+    WindowClosed(window::Id),
+    FocusChanged(bool),
+
+    // Terminal
+    InputChanged(String),
+    ClearInput,
+    MultilineEdited(text_editor::Action),
+    EnterSendsToggled(bool),
+    SendData,
+    PasteAndSend,
+    ClipboardRead(Option<String>),
+    ClearTerminal,
+    MarkerChanged(String),
+    InsertMarker,
+    SearchChanged(String),
+    SearchNext,
+    SearchPrev,
+    ToggleErrorPanel,
+    ErrorFilterChanged(String),
+    ClearErrors,
+    CancelClear,
+    UndoClear,
+    HexModeToggled(bool),
+    TrimOnSendToggled(bool),
+    SendPerLineToggled(bool),
+    LineDelayChanged(String),
+    // Carries the queue generation so steps of an aborted batch are ignored
+    SendNextLine(u64),
+    AbortLineQueue,
+    SendPrefixChanged(String),
+    SendSuffixChanged(String),
+    SendChecksumSelected(SendChecksum),
+    FillByteChanged(String),
+    FillCountChanged(String),
+    SendFill,
+    BytesInputChanged(String),
+    SendBytes,
+    RepeatCommandsChanged(String),
+    RepeatIntervalChanged(String),
+    RepeatToggled,
+    RepeatTick,
+    ClearInputOnSendToggled(bool),
+    KeepFocusToggled(bool),
+    SuppressRepeatsToggled(bool),
+    TimestampsToggled(bool),
+    LineTimestampsToggled(bool),
+    CrOverwriteToggled(bool),
+    DeltasToggled(bool),
+    SplitColumnsToggled(bool),
+    EolGlyphsToggled(bool),
+    DisplayModeSelected(DisplayMode),
+    // Shortcut between Text and Hex; other modes stay reachable through the picker
+    HexOutputToggled(bool),
+    HexdumpWidthSelected(usize),
+    AnsiColorsToggled(bool),
+    FollowOutputToggled(bool),
+    TerminalScrolled(scrollable::Viewport),
+    JumpToBottom,
+    ValueSparklineToggled(bool),
+    GroupExchangesToggled(bool),
+    ExchangeWindowChanged(String),
+    ApplyExchangeWindow,
+    PartialLineToggled(bool),
+    RawModeToggled(bool),
+    RawKey(Key, Modifiers),
+    LineEndingSelected(LineEnding),
+
+    // Settings
+    PortSelected(String),
+    BaudRateSelected(u32),
+    DataBitsSelected(DataBits),
+    ParitySelected(ParityOption),
+    StopBitsSelected(StopBits),
+    FlowControlSelected(FlowControl),
+    FramingPreset(DataBits, ParityOption, StopBits),
+    ExclusiveToggled(bool),
+    ReadOnlyToggled(bool),
+    ConnectPort,
+    RetryConnect(String, u32),
+    RecoverSend,
+    ReconnectOnSendErrorToggled(bool),
+    ResendAfterReconnectToggled(bool),
+    DisconnectPort,
+    Reopen,
+    DtrToggled(bool),
+    RtsToggled(bool),
+    ResetUsesRtsToggled(bool),
+    ResetPulseChanged(String),
+    ResetTarget,
+    ResetPulseSent(Result<(), String>),
+    RefreshPorts,
+    DumpPorts,
+    PollPorts,
+    QuickConnect,
+    PortsUpdated(Vec<String>),
+    AutoRefreshToggled(bool),
+    AutoConnectToggled(bool),
+    RecentPortsToggled(bool),
+    ConnectRecent(RecentPort),
+    ProfileSelected(ConnectionProfile),
+    ProfileNameChanged(String),
+    SaveProfile,
+    DeleteProfile,
+    PinnedUsbChanged(String),
+    ApplyPinnedUsb,
+    PinnedUsbToggled(bool),
+
+    // Monitor
+    StartMonitoring,
+    PingRequestChanged(String),
+    PingResponseChanged(String),
+    PingCountChanged(String),
+    StartPing,
+    StopPing,
+    CaptureSizeChanged(String),
+    StartCapture,
+    CancelCapture,
+    SaveCapture,
+    CaptureSaved(Result<Option<String>, String>),
+    StartTxRecording,
+    StopTxRecording,
+    RecordingSaved(Result<Option<String>, String>),
+    LoadRecording,
+    RecordingLoaded(Result<Option<(String, Vec<u8>)>, String>),
+    ReplaySpeedChanged(String),
+    StartReplay,
+    // Carries the replay generation so steps of a stopped replay are ignored
+    ReplayStep(u64),
+    StopReplay,
+    StopMonitoring,
+
+    // File
+    SaveLog,
+    LogPathChosen(Option<PathBuf>),
+    ExportDirectionSelected(ExportDirection),
+    ExportFormatSelected(ExportFormat),
+    ExportLineEndingSelected(SaveLineEnding),
+    AutosaveKbChanged(String),
+    AutosaveSecsChanged(String),
+    ApplyAutosave,
+
+    // Serial port
+    PortError(String),
+    // From the threads of the port opened as this generation; older ones are ignored
+    Port(u64, PortEvent),
+
+    StopAll,
+    LoadScript,
+    ScriptLoaded(Result<Option<(String, Vec<u8>)>, String>),
+    StopScript,
+    LoadByteHook,
+    ByteHookLoaded(Result<Option<(String, Vec<u8>)>, String>),
+    UnloadByteHook,
+    SaveChart,
+    ChartSaved(Result<Option<String>, String>),
+    SaveSession,
+    SessionSaved(Result<Option<String>, String>),
+    LoadSession,
+    SessionLoaded(Result<Option<(String, Vec<u8>)>, String>),
+    CloseLoadedSession,
+
+    // File transfer
+    SendFile(TransferKind),
+    FileChosen(TransferKind, Result<Option<(String, Vec<u8>)>, String>),
+    FileAsHexToggled(bool),
+    TransferProgress(usize),
+    TransferPaused(bool),
+    TransferReceived(Vec<u8>),
+    XonXoffToggled(bool),
+    TransferFinished(Result<(), String>),
+    CustomBaudChanged(String),
+    ScanBaud,
+    BaudScanStep(u32, Result<BaudScore, String>),
+    BaudScanFinished,
+    ApplyScannedBaud(u32),
+    CloseBaudScan,
+    ApplyCustomBaud,
+    VerifyFile,
+    VerifyFileChosen(Result<Option<(String, Vec<u8>)>, String>),
+    // Tagged with the hash run, so a file picked mid-hash doesn't get the old file's CRC
+    HashProgress(u64, usize),
+    HashFinished(u64, u32),
+    ExpectedCrcChanged(String),
+    SendVerified,
+    ConfirmLargeTransfer,
+    CancelLargeTransfer,
+
+    // Appearance
+    ColorInputChanged(LineKind, String),
+    MaxLineLenChanged(String),
+    ApplyMaxLineLen,
+    RxWrapChanged(String),
+    ApplyRxWrap,
+    PromptChanged(String),
+    ApplyPrompt,
+    PromptModeSelected(PromptMode),
+    RxFilterSelected(RxFilter),
+    NulHandlingSelected(NulHandling),
+    RxFilterRangeChanged(String),
+    ApplyRxFilterRange,
+    AlertPatternChanged(String),
+    ApplyAlertPattern,
+    AlertSoundToggled(bool),
+    AlertFlashToggled(bool),
+    IdleTimeoutChanged(String),
+    PollIntervalChanged(String),
+    ApplyPollInterval,
+    FrameDelimiterChanged(String),
+    ApplyFrameDelimiter,
+    ApplyIdleTimeout,
+    LargeFileChanged(String),
+    ConnectRetriesChanged(String),
+    ConnectRetryDelayChanged(String),
+    ApplyConnectRetry,
+    ApplyLargeFile,
+    ApplyColors,
+    TagPortsToggled(bool),
+    PortColorChanged(String),
+    ApplyPortColor,
+    ResetColors,
+
+    // Internal messages
+    Tick,
+    ClockTick,
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum WindowState {
+    #[default]
+    Terminal,
+    Settings,
+    Monitor,
+    FileView,
+    Compare,
+}
+
+// Lines of a multi-line input going out one at a time
+pub struct LineQueue {
+    pub payloads: Vec<(Vec<u8>, String)>,
+    pub index: usize,
+    pub delay: Duration,
 }
 
+// One entry of the error panel; kept apart from the log so clearing either leaves the other
 #[derive(Debug, Clone)]
-pub enum Message {
-    // UI messages
-    NoOp,
-}
-
-impl App {
-    pub fn new() -> (Self, Task<Message>) {
-        (
-            Self {
-                terminal: String::new(),
-                input: String::new(),
-                port: None,
-                selected_port: None,
+pub struct ErrorEntry {
+    pub time: String,
+    pub text: String,
+}
+// Oldest errors are dropped beyond this
+const MAX_ERRORS: usize = 200;
+
+const TERMINAL_SCROLL_ID: &str = "terminal";
+// How close to the bottom still counts as "at the bottom" for following output
+const FOLLOW_SLACK_PX: f32 = 20.0;
+
+// Accent colors for port tags, picked by port name until the user sets one
+const PORT_PALETTE: [Color; 6] = [
+    Color::from_rgb(0.95, 0.6, 0.2),
+    Color::from_rgb(0.3, 0.7, 0.95),
+    Color::from_rgb(0.75, 0.45, 0.95),
+    Color::from_rgb(0.4, 0.85, 0.6),
+    Color::from_rgb(0.95, 0.45, 0.6),
+    Color::from_rgb(0.85, 0.85, 0.35),
+];
+
+const COMMAND_INPUT_ID: &str = "command_input";
+
+// Embedded so hexdump and decimal columns line up on every system
+const TERMINAL_FONT_BYTES: &[u8] = include_bytes!("../fonts/jetbrains-mono.regular.ttf");
+const TERMINAL_FONT: Font = Font::with_name("JetBrains Mono");
+
+const CLEAR_CONFIRM_LINES: usize = 1000;
+// How long "Отменить очистку" stays offered after a clear
+const UNDO_CLEAR_WINDOW: Duration = Duration::from_secs(15);
+
+// Terminal state taken by the last clear, kept until new data arrives or the window ends
+#[derive(Debug, Clone)]
+pub struct ClearBackup {
+    pub lines: VecDeque<TerminalLine>,
+    pub log_written: usize,
+    pub last_push: Option<Instant>,
+    pub dirty: bool,
+    pub cleared_at: Instant,
+}
+
+impl ClearBackup {
+    fn is_fresh(&self) -> bool {
+        self.cleared_at.elapsed() < UNDO_CLEAR_WINDOW
+    }
+}
+const TRAFFIC_HISTORY_LEN: usize = 120;
+// Samples shown by the status bar sparkline
+const SPARKLINE_LEN: usize = 30;
+// Matches closer together than this are logged but don't beep or flash again
+const ALERT_MIN_INTERVAL: Duration = Duration::from_secs(3);
+const ALERT_FLASH: Duration = Duration::from_millis(600);
+// Port polling (and so redrawing) rate while no window of the app has focus
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Above this many queued bytes the TX queue indicator turns into a warning
+const TX_QUEUE_WARN_BYTES: usize = 4096;
+// Bytes left in the driver queue after a read that mean polling is falling behind
+const DRIVER_QUEUE_WARN: u32 = 4096;
+
+// RX/TX lamps lit when the byte counters moved since the previous tick
+#[derive(Debug, Clone, Default)]
+pub struct ActivityLeds {
+    rx_count: u64,
+    tx_count: u64,
+    pub rx: bool,
+    pub tx: bool,
+}
+
+impl ActivityLeds {
+    pub fn update(&mut self, rx_count: u64, tx_count: u64) {
+        self.rx = rx_count != self.rx_count;
+        self.tx = tx_count != self.tx_count;
+        self.rx_count = rx_count;
+        self.tx_count = tx_count;
+    }
+}
+
+#[derive(Default)]
+pub struct ComTerminal {
+    current_window: WindowState,
+    main_window: Option<window::Id>,
+    // Set once the embedded terminal font finished loading
+    terminal_font_loaded: bool,
+    // Detached read-only mirror of the terminal, e.g. for a projector
+    log_window: Option<window::Id>,
+    input_text: String,
+    // Backs the input while Enter inserts newlines; its text mirrors into `input_text`
+    multiline_input: text_editor::Content,
+    hex_mode: bool,
+    hex_error: Option<String>,
+    trim_on_send: bool,
+    send_per_line: bool,
+    line_delay_input: String,
+    line_queue: Option<LineQueue>,
+    line_queue_generation: u64,
+    send_recovery: Option<SendRecovery>,
+    clear_input_on_send: bool,
+    suppress_repeats: bool,
+    show_timestamps: bool,
+    // A bare '\r' rewinds the current RX line like a real terminal does
+    cr_overwrite: bool,
+    cr_pending: bool,
+    show_deltas: bool,
+    // TX in its own column; rows still follow the shared timeline
+    split_columns: bool,
+    show_eol: bool,
+    display_mode: DisplayMode,
+    // Show the unterminated frame under the log while it is still arriving
+    show_partial: bool,
+    last_push: Option<Instant>,
+    // Latest send and when it went out, for tagging the responses that follow
+    open_exchange: Option<(u64, Instant)>,
+    next_exchange: u64,
+    // Every keystroke goes straight to the port instead of through the input field
+    raw_mode: bool,
+    fill_byte_input: String,
+    fill_count_input: String,
+    // One-off byte list, independent of the main input's text/hex mode
+    bytes_input: String,
+    // Commands sent round-robin on each repeat tick, separated by ';'
+    repeat_commands_input: String,
+    repeat_interval_input: String,
+    repeat_running: bool,
+    repeat_index: usize,
+    line_ending: LineEnding,
+    terminal_output: VecDeque<TerminalLine>,
+    errors: VecDeque<ErrorEntry>,
+    show_errors: bool,
+    error_filter: String,
+    search_query: String,
+    marker_input: String,
+    markers_inserted: usize,
+    // Position of the highlighted hit within the current matches
+    search_match: usize,
+    // Whether the terminal view sits at the bottom and should track new output
+    following: bool,
+    last_scroll_y: f32,
+    // Set on every append and reset by saving the log
+    dirty: bool,
+    clear_backup: Option<ClearBackup>,
+    // A large unsaved buffer needs a second click on "Очистить"
+    clear_armed: bool,
+    config: AppConfig,
+    color_inputs: LineColors,
+    port_color_input: String,
+    max_line_len_input: String,
+    rx_wrap_input: String,
+    exchange_window_input: String,
+    idle_timeout_input: String,
+    large_file_input: String,
+    connect_retries_input: String,
+    connect_retry_delay_input: String,
+    poll_interval_input: String,
+    frame_delimiter_input: String,
+    // Bytes after the last delimiter, waiting for the rest of their frame
+    frame_buffer: Vec<u8>,
+    // The last received chunk did not end with '\n'
+    mid_line: bool,
+    last_activity: Option<Instant>,
+    port_settings: PortSettings,
+    available_ports: Vec<String>,
+    port_infos: Vec<SerialPortInfo>,
+    pinned_usb_input: String,
+    // Port opened because the pinned device appeared; closed again when it vanishes
+    pinned_port: Option<String>,
+    pinned_present: bool,
+    auto_refresh_ports: bool,
+    // Modem control lines as last set through the UI
+    dtr: bool,
+    rts: bool,
+    reset_uses_rts: bool,
+    reset_pulse_input: String,
+    baud_rates: Vec<u32>,
+    custom_baud_input: String,
+    baud_scan: Option<BaudScan>,
+    // Rate the driver reports after opening; may differ from the requested one
+    actual_baud: Option<u32>,
+    // Framing the open port was opened with; the selectors may already hold the next one
+    open_framing: Option<String>,
+    monitoring: bool,
+    received_bytes: u64,
+    sent_bytes: u64,
+    log_file_path: Option<String>,
+    // Lines of `terminal_output` already written to the log file
+    log_written: usize,
+    export_direction: ExportDirection,
+    export_format: ExportFormat,
+    export_line_ending: SaveLineEnding,
+    rx_since_flush: u64,
+    last_flush: Option<Instant>,
+    autosave_kb_input: String,
+    autosave_secs_input: String,
+    serial_port: Option<PortIo<Box<dyn SerialPort>>>,
+    // Bumped on every open so events from a port closed since are dropped
+    port_generation: u64,
+    // Writes handed to the writer thread but not yet on the wire
+    tx_queued: usize,
+    tx_queued_bytes: usize,
+    // Events of a freshly opened port, waiting to be attached as a task by `update`
+    port_events: Option<futures::channel::mpsc::UnboundedReceiver<PortEvent>>,
+    session: Option<ActiveSession>,
+    session_history: VecDeque<SessionRecord>,
+    // A session file opened for viewing; never mixed into the live log
+    loaded_session: Option<(String, SessionSnapshot)>,
+    // Two saved logs opened for comparison and their diff, recomputed when either changes
+    compare_logs: [Option<(String, Vec<String>)>; 2],
+    compare_diff: Option<Result<Vec<line_diff::DiffLine>, String>>,
+    profile_name_input: String,
+    selected_profile: Option<ConnectionProfile>,
+    last_rx_frame: Vec<u8>,
+    // Exact bytes of the last write as they went on the wire
+    last_tx_frame: Vec<u8>,
+    file_transfer: Option<FileTransfer>,
+    // Joined when the port closes mid-transfer, since the thread holds the device open
+    transfer_thread: Option<std::thread::JoinHandle<()>>,
+    verified_file: Option<VerifiedFile>,
+    hash_generation: u64,
+    // A file over the size limit, held until the user confirms the send
+    pending_transfer: Option<(TransferKind, String, Vec<u8>)>,
+    expected_crc_input: String,
+    // Raw sends parse the file as hex text instead of sending it literally
+    file_as_hex: bool,
+    // Raw file sends stop on XOFF from the device; only offered with software flow control
+    xon_xoff: bool,
+    script: Option<ScriptRun>,
+    #[cfg(feature = "scripting")]
+    byte_hook: Option<byte_hook::ByteHook>,
+    ping_request_input: String,
+    ping_response_input: String,
+    ping_count_input: String,
+    ping: Option<PingRun>,
+    capture_size_input: String,
+    capture: Option<ByteCapture>,
+    // Taps write_bytes while recording; the Instant is when recording started
+    tx_recording: Option<(Instant, TxRecording)>,
+    loaded_recording: Option<(String, TxRecording)>,
+    replay: Option<Replay>,
+    replay_generation: u64,
+    replay_speed_input: String,
+    activity: ActivityLeds,
+    // Driver input queue after the last poll; None when the backend can't report it
+    driver_queue: Option<u32>,
+    // Bytes received with a bad stop bit or parity on this connection; None when the port
+    // can't mark them. Recent ones, in batches, detect a spike
+    framing_errors: Option<u64>,
+    recent_framing_errors: VecDeque<(Instant, u64)>,
+    // Unfocused: tick rarely and hand received bytes over in larger batches
+    focused: bool,
+    alert_pattern_input: String,
+    prompt_input: String,
+    rx_filter_range_input: String,
+    send_prefix_input: String,
+    send_suffix_input: String,
+    // Set by the receive path, turned into sound/flash on the next tick
+    alert_pending: bool,
+    last_alert: Option<Instant>,
+    flash_until: Option<Instant>,
+    // Bytes per second, one sample per clock tick, shared by the chart and its export
+    traffic: VecDeque<(u64, u64)>,
+    traffic_counts: (u64, u64),
+    // Decoded chars and completed lines: running totals, their values at the last
+    // sample, and the resulting per-second rates
+    received_chars: u64,
+    received_lines: u64,
+    text_counts: (u64, u64),
+    text_rates: (u64, u64),
+    traffic_svg: Option<svg::Handle>,
+    // Latest numbers parsed from received lines and their rendered trend
+    value_samples: VecDeque<f64>,
+    sparkline_svg: Option<svg::Handle>,
+    cancel_flag: Arc<AtomicBool>,
+}
+
+impl ComTerminal {
+    // Loads the saved config, lists the ports and may auto-connect
+    fn new() -> Self {
+        let mut terminal = Self::with_config(AppConfig::load());
+
+        terminal.push_line(LineKind::Info, "=== COM Terminal запущен ===".to_string());
+        terminal.push_line(LineKind::Info, "Загружаем список COM портов...".to_string());
+
+        // Initial loading of ports
+        match available_ports() {
+            Ok(ports) => {
+                terminal.available_ports = ports.iter().map(|p| p.port_name.clone()).collect();
+                terminal.port_infos = ports;
+                if terminal.available_ports.is_empty() {
+                    terminal.push_line(LineKind::Info, "⚠️ COM порты не найдены".to_string());
+                } else {
+                    terminal.push_line(
+                        LineKind::Info,
+                        format!("📋 Найдено портов: {}", terminal.available_ports.len()),
+                    );
+                }
+            }
+            Err(e) => {
+                terminal.push_line(
+                    LineKind::Error,
+                    format!("❌ Ошибка получения списка портов: {}", e),
+                );
+            }
+        }
+        terminal.restore_last_port();
+        terminal.check_pinned_device();
+
+        terminal
+    }
+
+    // Just the state, with no disk or port access
+    fn with_config(config: AppConfig) -> Self {
+        Self {
+            current_window: WindowState::Terminal,
+            main_window: None,
+            terminal_font_loaded: false,
+            log_window: None,
+            input_text: String::new(),
+            multiline_input: text_editor::Content::new(),
+            hex_mode: false,
+            hex_error: None,
+            trim_on_send: false,
+            send_per_line: false,
+            line_delay_input: "0".to_string(),
+            line_queue: None,
+            send_recovery: None,
+            line_queue_generation: 0,
+            clear_input_on_send: true,
+            suppress_repeats: false,
+            show_timestamps: false,
+            cr_overwrite: false,
+            cr_pending: false,
+            show_deltas: false,
+            split_columns: false,
+            show_eol: false,
+            display_mode: DisplayMode::Text,
+            show_partial: true,
+            last_push: None,
+            open_exchange: None,
+            next_exchange: 0,
+            raw_mode: false,
+            fill_byte_input: "0x55".to_string(),
+            fill_count_input: "16".to_string(),
+            bytes_input: String::new(),
+            repeat_commands_input: String::new(),
+            repeat_interval_input: "1000".to_string(),
+            repeat_running: false,
+            repeat_index: 0,
+            line_ending: LineEnding::None,
+            terminal_output: VecDeque::new(),
+            clear_backup: None,
+            errors: VecDeque::new(),
+            show_errors: false,
+            error_filter: String::new(),
+            search_query: String::new(),
+            marker_input: String::new(),
+            markers_inserted: 0,
+            search_match: 0,
+            following: true,
+            last_scroll_y: 0.0,
+            dirty: false,
+            clear_armed: false,
+            color_inputs: config.line_colors.clone(),
+            port_color_input: String::new(),
+            max_line_len_input: config.max_line_len.to_string(),
+            rx_wrap_input: config.rx_wrap.to_string(),
+            exchange_window_input: config.exchange_window_ms.to_string(),
+            alert_pattern_input: config.alert_pattern.clone(),
+            prompt_input: config.prompt.clone(),
+            rx_filter_range_input: byte_range_label(config.rx_filter_range),
+            send_prefix_input: config.send_prefix.clone(),
+            send_suffix_input: config.send_suffix.clone(),
+            idle_timeout_input: config.idle_timeout_min.to_string(),
+            large_file_input: config.large_file_kb.to_string(),
+            connect_retries_input: config.connect_retries.to_string(),
+            connect_retry_delay_input: config.connect_retry_delay_ms.to_string(),
+            autosave_kb_input: config.autosave_kb.to_string(),
+            autosave_secs_input: config.autosave_secs.to_string(),
+            poll_interval_input: config.poll_interval_ms.to_string(),
+            frame_delimiter_input: delimiter_label(config.frame_delimiter),
+            pinned_usb_input: config.pinned_usb.map(vid_pid_label).unwrap_or_default(),
+            frame_buffer: Vec::new(),
+            mid_line: false,
+            last_activity: None,
+            config,
+            port_settings: PortSettings::default(),
+            available_ports: vec![],
+            port_infos: vec![],
+            pinned_port: None,
+            pinned_present: false,
+            auto_refresh_ports: false,
+            dtr: false,
+            rts: false,
+            reset_uses_rts: false,
+            reset_pulse_input: DEFAULT_RESET_PULSE_MS.to_string(),
+            baud_rates: vec![
+                9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600, 1000000, 1500000,
+                2000000, 3000000,
+            ],
+            monitoring: false,
+            received_bytes: 0,
+            sent_bytes: 0,
+            log_file_path: None,
+            log_written: 0,
+            export_direction: ExportDirection::Both,
+            export_format: ExportFormat::Text,
+            export_line_ending: SaveLineEnding::AsReceived,
+            rx_since_flush: 0,
+            last_flush: Some(Instant::now()),
+            serial_port: None,
+            port_generation: 0,
+            tx_queued: 0,
+            tx_queued_bytes: 0,
+            port_events: None,
+            session: None,
+            session_history: VecDeque::new(),
+            loaded_session: None,
+            compare_logs: [None, None],
+            compare_diff: None,
+            profile_name_input: String::new(),
+            selected_profile: None,
+            last_rx_frame: Vec::new(),
+            last_tx_frame: Vec::new(),
+            file_transfer: None,
+            transfer_thread: None,
+            verified_file: None,
+            hash_generation: 0,
+            pending_transfer: None,
+            custom_baud_input: String::new(),
+            baud_scan: None,
+            actual_baud: None,
+            open_framing: None,
+            expected_crc_input: String::new(),
+            file_as_hex: false,
+            xon_xoff: true,
+            script: None,
+            #[cfg(feature = "scripting")]
+            byte_hook: None,
+            ping_request_input: "PING".to_string(),
+            ping_response_input: "PONG".to_string(),
+            ping_count_input: "10".to_string(),
+            ping: None,
+            capture_size_input: "256".to_string(),
+            capture: None,
+            tx_recording: None,
+            loaded_recording: None,
+            replay: None,
+            replay_generation: 0,
+            replay_speed_input: "1".to_string(),
+            activity: ActivityLeds::default(),
+            driver_queue: None,
+            framing_errors: None,
+            recent_framing_errors: VecDeque::new(),
+            focused: true,
+            alert_pending: false,
+            last_alert: None,
+            flash_until: None,
+            traffic: VecDeque::new(),
+            traffic_counts: (0, 0),
+            received_chars: 0,
+            received_lines: 0,
+            text_counts: (0, 0),
+            text_rates: (0, 0),
+            traffic_svg: None,
+            value_samples: VecDeque::new(),
+            sparkline_svg: None,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // Any message can add output, so following the tail is handled once for all of them
+    fn update(&mut self, message: Message) -> Task<Message> {
+        let before = self.output_marker();
+        let mut task = self.handle_message(message);
+        if let Some(events) = self.take_port_events() {
+            task = Task::batch([task, events]);
+        }
+        if self.config.follow_output && self.following && self.output_marker() != before {
+            return Task::batch([task, self.snap_to_bottom()]);
+        }
+        task
+    }
+
+    // Any message may open a port, so its event stream is picked up once here
+    fn take_port_events(&mut self) -> Option<Task<Message>> {
+        let events = self.port_events.take()?;
+        let generation = self.port_generation;
+        Some(Task::stream(events).map(move |event| Message::Port(generation, event)))
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
+        match message {
+            // Transfer, automation, logging and connection messages live with their state
+            message @ (Message::SendFile(..)
+            | Message::FileChosen(..)
+            | Message::VerifyFile
+            | Message::VerifyFileChosen(..)
+            | Message::HashProgress(..)
+            | Message::HashFinished(..)
+            | Message::ExpectedCrcChanged(..)
+            | Message::SendVerified
+            | Message::ConfirmLargeTransfer
+            | Message::CancelLargeTransfer
+            | Message::FileAsHexToggled(..)
+            | Message::XonXoffToggled(..)
+            | Message::TransferProgress(..)
+            | Message::TransferPaused(..)
+            | Message::TransferReceived(..)
+            | Message::TransferFinished(..)) => return self.update_transfer(message),
+            message @ (Message::PingRequestChanged(..)
+            | Message::PingResponseChanged(..)
+            | Message::PingCountChanged(..)
+            | Message::StartPing
+            | Message::StopPing
+            | Message::LoadByteHook
+            | Message::ByteHookLoaded(..)
+            | Message::UnloadByteHook
+            | Message::LoadScript
+            | Message::ScriptLoaded(..)
+            | Message::StopScript) => return self.update_automation(message),
+            message @ (Message::SaveLog
+            | Message::LogPathChosen(..)
+            | Message::ExportDirectionSelected(..)
+            | Message::ExportFormatSelected(..)
+            | Message::ExportLineEndingSelected(..)
+            | Message::AutosaveKbChanged(..)
+            | Message::AutosaveSecsChanged(..)
+            | Message::ApplyAutosave
+            | Message::SaveSession
+            | Message::CaptureSizeChanged(..)
+            | Message::StartCapture
+            | Message::CancelCapture
+            | Message::SaveCapture
+            | Message::StartTxRecording
+            | Message::StopTxRecording
+            | Message::RecordingSaved(..)
+            | Message::LoadRecording
+            | Message::RecordingLoaded(..)
+            | Message::ReplaySpeedChanged(..)
+            | Message::StartReplay
+            | Message::ReplayStep(..)
+            | Message::StopReplay
+            | Message::CaptureSaved(..)
+            | Message::SessionSaved(..)
+            | Message::LoadSession
+            | Message::SessionLoaded(..)
+            | Message::CloseLoadedSession) => return self.update_logging(message),
+            message @ (Message::PortSelected(..)
+            | Message::BaudRateSelected(..)
+            | Message::CustomBaudChanged(..)
+            | Message::ApplyCustomBaud
+            | Message::ScanBaud
+            | Message::BaudScanStep(..)
+            | Message::BaudScanFinished
+            | Message::ApplyScannedBaud(..)
+            | Message::CloseBaudScan
+            | Message::DataBitsSelected(..)
+            | Message::ParitySelected(..)
+            | Message::StopBitsSelected(..)
+            | Message::FlowControlSelected(..)
+            | Message::ReadOnlyToggled(..)
+            | Message::ExclusiveToggled(..)
+            | Message::FramingPreset(..)
+            | Message::ConnectPort
+            | Message::RetryConnect(..)
+            | Message::RecoverSend
+            | Message::ReconnectOnSendErrorToggled(..)
+            | Message::ResendAfterReconnectToggled(..)
+            | Message::DisconnectPort
+            | Message::Reopen
+            | Message::RefreshPorts
+            | Message::DumpPorts
+            | Message::QuickConnect
+            | Message::PollPorts
+            | Message::PortsUpdated(..)
+            | Message::AutoRefreshToggled(..)
+            | Message::DtrToggled(..)
+            | Message::RtsToggled(..)
+            | Message::ResetUsesRtsToggled(..)
+            | Message::ResetPulseChanged(..)
+            | Message::ResetTarget
+            | Message::ResetPulseSent(..)
+            | Message::AutoConnectToggled(..)
+            | Message::RecentPortsToggled(..)
+            | Message::ConnectRecent(..)
+            | Message::ProfileSelected(..)
+            | Message::ProfileNameChanged(..)
+            | Message::SaveProfile
+            | Message::DeleteProfile
+            | Message::PinnedUsbChanged(..)
+            | Message::ApplyPinnedUsb
+            | Message::PinnedUsbToggled(..)
+            | Message::PortError(..)
+            | Message::Port(..)
+            | Message::PollIntervalChanged(..)
+            | Message::ApplyPollInterval
+            | Message::ConnectRetriesChanged(..)
+            | Message::ConnectRetryDelayChanged(..)
+            | Message::ApplyConnectRetry) => return self.update_connection(message),
+            Message::ShowTerminal => {
+                self.current_window = WindowState::Terminal;
+            }
+            Message::ShowSettings => {
+                self.current_window = WindowState::Settings;
+            }
+            Message::ShowMonitor => {
+                self.current_window = WindowState::Monitor;
+            }
+            Message::ShowFileView => {
+                self.current_window = WindowState::FileView;
+            }
+            Message::ShowCompare => {
+                self.current_window = WindowState::Compare;
+            }
+            Message::LoadCompareLog(side) => {
+                return Task::perform(async { pick_file_bytes() }, move |result| {
+                    Message::CompareLogLoaded(side, result)
+                });
+            }
+            Message::CompareLogLoaded(side, Ok(Some((name, data)))) => {
+                let lines = String::from_utf8_lossy(&data)
+                    .lines()
+                    .map(str::to_string)
+                    .collect();
+                self.compare_logs[side] = Some((name, lines));
+                self.compare_diff = match &self.compare_logs {
+                    [Some((_, old)), Some((_, new))] => Some(line_diff::diff(old, new)),
+                    _ => None,
+                };
+            }
+            Message::CompareLogLoaded(_, Ok(None)) => {}
+            Message::CompareLogLoaded(_, Err(e)) => {
+                self.push_line(LineKind::Error, format!("❌ Ошибка чтения файла: {}", e));
+            }
+            Message::ToggleLogWindow => {
+                if let Some(id) = self.log_window.take() {
+                    return window::close(id);
+                }
+                let (id, open) = window::open(window::Settings {
+                    size: Size::new(1000.0, 700.0),
+                    ..window::Settings::default()
+                });
+                self.log_window = Some(id);
+                return open.map(Message::WindowOpened);
+            }
+            Message::WindowOpened(_) => {}
+            Message::FontLoaded(result) => {
+                self.terminal_font_loaded = result.is_ok();
+                if result.is_err() {
+                    self.push_line(
+                        LineKind::Error,
+                        "⚠️ Шрифт JetBrains Mono не загружен, используется системный моноширинный"
+                            .to_string(),
+                    );
+                }
+            }
+            Message::MonospaceToggled(enabled) => {
+                self.config.monospace_terminal = enabled;
+                self.save_config();
+            }
+            Message::FocusChanged(focused) => {
+                // Switching between our two windows reports Unfocused then Focused
+                self.focused = focused;
+                if let Some(port) = &self.serial_port {
+                    port.set_batch_window(self.tick_interval());
+                }
+            }
+            Message::WindowClosed(id) => {
+                if self.main_window == Some(id) {
+                    return iced::exit();
+                }
+                if self.log_window == Some(id) {
+                    self.log_window = None;
+                }
+            }
+            Message::InputChanged(text) => {
+                self.input_text = text;
+                self.validate_input();
+            }
+            Message::ClearInput => {
+                self.input_text.clear();
+                self.multiline_input = text_editor::Content::new();
+                self.validate_input();
+            }
+            Message::HexModeToggled(enabled) => {
+                self.hex_mode = enabled;
+                self.validate_input();
+            }
+            Message::SendData => {
+                if self.file_transfer.is_some() {
+                    self.push_line(
+                        LineKind::Info,
+                        "⚠️ Дождитесь окончания передачи файла".to_string(),
+                    );
+                } else if self.line_queue.is_some() {
+                    self.push_line(
+                        LineKind::Info,
+                        "⚠️ Дождитесь отправки предыдущих строк".to_string(),
+                    );
+                } else if self.send_per_line
+                    && self.input_text.contains('\n')
+                    && self.port_settings.connected
+                {
+                    // Everything is checked before the first line goes out
+                    let queue = parse_line_delay(&self.line_delay_input).and_then(|delay| {
+                        let payloads = self
+                            .input_text
+                            .lines()
+                            .map(|line| self.build_line_payload(line))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(LineQueue {
+                            payloads,
+                            index: 0,
+                            delay,
+                        })
+                    });
+                    match queue {
+                        Ok(queue) => {
+                            self.line_queue = Some(queue);
+                            self.line_queue_generation += 1;
+                            if self.clear_input_on_send {
+                                self.input_text.clear();
+                                self.multiline_input = text_editor::Content::new();
+                            }
+                            self.validate_input();
+                            return self.update(Message::SendNextLine(self.line_queue_generation));
+                        }
+                        Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+                    }
+                } else if !self.input_text.is_empty() && self.port_settings.connected {
+                    let (data, echo) = match self.build_payload(&self.input_text) {
+                        Ok(payload) => payload,
+                        Err(e) => {
+                            self.push_line(LineKind::Error, format!("❌ Ошибка HEX: {}", e));
+                            return Task::none();
+                        }
+                    };
+                    self.send_with_echo(echo, &data);
+                    if self.clear_input_on_send {
+                        self.input_text.clear();
+                        self.multiline_input = text_editor::Content::new();
+                    }
+                    self.validate_input();
+                    // The multi-line editor sends on a key binding and never loses focus
+                    if self.config.keep_input_focus && self.config.enter_sends {
+                        return text_input::focus(text_input::Id::new(COMMAND_INPUT_ID));
+                    }
+                }
+            }
+            Message::SendNextLine(generation) => {
+                if generation != self.line_queue_generation {
+                    return Task::none();
+                }
+                if self.file_transfer.is_some() {
+                    self.stop_line_queue("прервана: идет передача файла");
+                    return Task::none();
+                }
+                let Some(queue) = self.line_queue.as_mut() else {
+                    return Task::none();
+                };
+                let (data, echo) = queue.payloads[queue.index].clone();
+                queue.index += 1;
+                let delay = queue.delay;
+                let done = queue.index >= queue.payloads.len();
+                if !self.can_transmit() {
+                    self.stop_line_queue("прервана: порт недоступен");
+                    return Task::none();
+                }
+                self.send_with_echo(echo, &data);
+                if done {
+                    self.line_queue = None;
+                    return Task::none();
+                }
+                return Task::perform(tokio::time::sleep(delay), move |_| {
+                    Message::SendNextLine(generation)
+                });
+            }
+            Message::AbortLineQueue => {
+                self.stop_line_queue("прервана");
+            }
+            Message::PasteAndSend => {
+                return iced::clipboard::read().map(Message::ClipboardRead);
+            }
+            Message::ClipboardRead(contents) => {
+                let contents = contents.unwrap_or_default();
+                if contents.is_empty() {
+                    self.push_line(LineKind::Info, "📋 Буфер обмена пуст".to_string());
+                } else if self.file_transfer.is_some() {
+                    self.push_line(
+                        LineKind::Info,
+                        "⚠️ Дождитесь окончания передачи файла".to_string(),
+                    );
+                } else if self.port_settings.connected {
+                    // Same path as the input field, so HEX mode and line endings apply
+                    match self.build_payload(&contents) {
+                        Ok((data, echo)) => self.send_with_echo(echo, &data),
+                        Err(e) => self.push_line(LineKind::Error, format!("❌ Ошибка HEX: {}", e)),
+                    }
+                } else {
+                    self.push_line(
+                        LineKind::Error,
+                        "❌ Вставка не отправлена: порт не открыт".to_string(),
+                    );
+                }
+            }
+            Message::FillByteChanged(value) => {
+                self.fill_byte_input = value;
+            }
+            Message::FillCountChanged(value) => {
+                self.fill_count_input = value;
+            }
+            Message::SendFill => {
+                if !self.port_settings.connected || self.file_transfer.is_some() {
+                    return Task::none();
+                }
+                match parse_fill(&self.fill_byte_input, &self.fill_count_input) {
+                    Ok((byte, count)) => {
+                        self.send_with_echo(
+                            format!("0x{:02X} × {} ({} байт)", byte, count, count),
+                            &vec![byte; count],
+                        );
+                    }
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+                }
+            }
+            Message::BytesInputChanged(value) => {
+                self.bytes_input = value;
+            }
+            Message::SendBytes => {
+                if !self.port_settings.connected || self.file_transfer.is_some() {
+                    return Task::none();
+                }
+                match parse_byte_list(&self.bytes_input) {
+                    Ok(bytes) => {
+                        self.send_with_echo(format!("[HEX] {}", hex::bytes_to_hex(&bytes)), &bytes);
+                    }
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+                }
+            }
+            Message::RepeatCommandsChanged(value) => {
+                self.repeat_commands_input = value;
+            }
+            Message::RepeatIntervalChanged(value) => {
+                self.repeat_interval_input = value;
+            }
+            Message::RepeatToggled => {
+                if self.repeat_running {
+                    self.stop_repeat();
+                } else if self.port_settings.connected && self.port_settings.read_only {
+                    self.push_line(
+                        LineKind::Error,
+                        "❌ Порт открыт только для чтения".to_string(),
+                    );
+                } else if self.can_transmit() {
+                    self.repeat_running = true;
+                    self.repeat_index = 0;
+                    self.push_line(
+                        LineKind::Info,
+                        "🔁 Циклическая отправка запущена".to_string(),
+                    );
+                }
+            }
+            Message::RepeatTick => {
+                if !self.repeat_running || self.file_transfer.is_some() {
+                    return Task::none();
+                }
+                // The list is re-read every tick so it can be edited while running
+                let commands = parse_command_list(&self.repeat_commands_input);
+                if commands.is_empty() {
+                    self.push_line(LineKind::Error, "❌ Список команд пуст".to_string());
+                    self.stop_repeat();
+                    return Task::none();
+                }
+                let index = self.repeat_index % commands.len();
+                match self.build_payload(&commands[index]) {
+                    Ok((data, echo)) => {
+                        self.send_with_echo(
+                            format!("[{}/{}] {}", index + 1, commands.len(), echo),
+                            &data,
+                        );
+                    }
+                    Err(e) => {
+                        self.push_line(LineKind::Error, format!("❌ Команда {}: {}", index + 1, e))
+                    }
+                }
+                self.repeat_index = (index + 1) % commands.len();
+            }
+            Message::MultilineEdited(action) => {
+                self.multiline_input.perform(action);
+                let text = self.multiline_input.text();
+                // The editor always reports a trailing newline
+                self.input_text = text.strip_suffix('\n').unwrap_or(&text).to_string();
+                self.validate_input();
+            }
+            Message::EnterSendsToggled(enabled) => {
+                self.config.enter_sends = enabled;
+                self.save_config();
+                if !enabled {
+                    self.multiline_input = text_editor::Content::with_text(&self.input_text);
+                } else if self.input_text.contains('\n') {
+                    // A single-line field can't hold the breaks, so the lines become one
+                    // space-separated command
+                    self.input_text = self.input_text.replace('\n', " ");
+                }
+            }
+            Message::TrimOnSendToggled(enabled) => {
+                self.trim_on_send = enabled;
+            }
+            Message::SendPerLineToggled(enabled) => {
+                self.send_per_line = enabled;
+            }
+            Message::LineDelayChanged(value) => {
+                self.line_delay_input = value;
+            }
+            Message::SendPrefixChanged(value) => {
+                if parse_escapes(&value).is_ok() {
+                    self.config.send_prefix = value.clone();
+                    self.save_config();
+                }
+                self.send_prefix_input = value;
+            }
+            Message::SendSuffixChanged(value) => {
+                // Only well-formed suffixes reach the config; the field shows the error otherwise
+                if parse_escapes(&value).is_ok() {
+                    self.config.send_suffix = value.clone();
+                    self.save_config();
+                }
+                self.send_suffix_input = value;
+            }
+            Message::SendChecksumSelected(checksum) => {
+                self.config.send_checksum = checksum;
+                self.save_config();
+            }
+            Message::ClearInputOnSendToggled(enabled) => {
+                self.clear_input_on_send = enabled;
+            }
+            Message::KeepFocusToggled(enabled) => {
+                self.config.keep_input_focus = enabled;
+                self.save_config();
+            }
+            Message::SuppressRepeatsToggled(enabled) => {
+                self.suppress_repeats = enabled;
+            }
+            Message::TimestampsToggled(enabled) => {
+                self.show_timestamps = enabled;
+            }
+            Message::LineTimestampsToggled(enabled) => {
+                self.config.line_timestamps = enabled;
+                self.save_config();
+            }
+            Message::CrOverwriteToggled(enabled) => {
+                self.cr_overwrite = enabled;
+                self.cr_pending = false;
+            }
+            Message::DeltasToggled(enabled) => {
+                self.show_deltas = enabled;
+            }
+            Message::SplitColumnsToggled(enabled) => {
+                self.split_columns = enabled;
+            }
+            Message::EolGlyphsToggled(enabled) => {
+                self.show_eol = enabled;
+            }
+            Message::DisplayModeSelected(mode) => {
+                self.display_mode = mode;
+                // Escape codes mean nothing in a byte view
+                if mode != DisplayMode::Text && self.config.ansi_colors {
+                    self.config.ansi_colors = false;
+                    self.save_config();
+                }
+            }
+            Message::HexOutputToggled(enabled) => {
+                let mode = if enabled {
+                    DisplayMode::Hex
+                } else {
+                    DisplayMode::Text
+                };
+                return self.update(Message::DisplayModeSelected(mode));
+            }
+            Message::HexdumpWidthSelected(width) => {
+                self.config.hexdump_width = hexdump_width(width);
+                self.save_config();
+            }
+            Message::AnsiColorsToggled(enabled) => {
+                self.config.ansi_colors = enabled;
+                if enabled {
+                    self.display_mode = DisplayMode::Text;
+                }
+                self.save_config();
+            }
+            Message::FollowOutputToggled(enabled) => {
+                self.config.follow_output = enabled;
+                self.save_config();
+                if enabled {
+                    self.following = true;
+                    return self.snap_to_bottom();
+                }
+            }
+            Message::TerminalScrolled(viewport) => {
+                // Growing content also reports a viewport; only a moved offset is the user's
+                let y = viewport.absolute_offset().y;
+                if y != self.last_scroll_y {
+                    self.last_scroll_y = y;
+                    let bottom = viewport.content_bounds().height - viewport.bounds().height;
+                    self.following = y >= bottom - FOLLOW_SLACK_PX;
+                }
+            }
+            Message::JumpToBottom => {
+                self.following = true;
+                return self.snap_to_bottom();
+            }
+            Message::ValueSparklineToggled(enabled) => {
+                self.config.value_sparkline = enabled;
+                self.save_config();
+            }
+            Message::GroupExchangesToggled(enabled) => {
+                self.config.group_exchanges = enabled;
+                self.save_config();
+            }
+            Message::ExchangeWindowChanged(value) => {
+                self.exchange_window_input = value;
+            }
+            Message::ApplyExchangeWindow => {
+                match parse_exchange_window(&self.exchange_window_input) {
+                    Ok(window_ms) => {
+                        self.config.exchange_window_ms = window_ms;
+                        self.save_config();
+                        self.push_line(
+                            LineKind::Info,
+                            if window_ms == 0 {
+                                "🔗 Ответом считается всё до следующей отправки".to_string()
+                            } else {
+                                format!("🔗 Ответом считается принятое в течение {} мс", window_ms)
+                            },
+                        );
+                    }
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+                }
+            }
+            Message::PartialLineToggled(enabled) => {
+                self.show_partial = enabled;
+            }
+            Message::RawModeToggled(enabled) => {
+                self.raw_mode = enabled;
+                self.push_line(
+                    LineKind::Info,
+                    if enabled {
+                        "⌨️ RAW режим: нажатия клавиш отправляются сразу".to_string()
+                    } else {
+                        "⌨️ RAW режим выключен".to_string()
+                    },
+                );
+            }
+            Message::RawKey(key, modifiers) => {
+                if !self.raw_mode || !self.can_transmit() || self.file_transfer.is_some() {
+                    return Task::none();
+                }
+                if let Some(data) = raw_key_bytes(&key, modifiers, self.line_ending) {
+                    // Keystrokes are not echoed line by line; only failures are logged
+                    self.write_bytes(&data, WriteKind::Quiet);
+                }
+            }
+            Message::LineEndingSelected(ending) => {
+                self.line_ending = ending;
+            }
+            Message::ClearTerminal => {
+                if self.dirty
+                    && self.terminal_output.len() > CLEAR_CONFIRM_LINES
+                    && !self.clear_armed
+                {
+                    self.clear_armed = true;
+                    return Task::none();
+                }
+                self.clear_armed = false;
+                self.clear_backup = Some(ClearBackup {
+                    lines: std::mem::take(&mut self.terminal_output),
+                    log_written: self.log_written,
+                    last_push: self.last_push,
+                    dirty: self.dirty,
+                    cleared_at: Instant::now(),
+                });
+                self.log_written = 0;
+                self.last_push = None;
+                self.push_line(LineKind::Info, "=== Терминал очищен ===".to_string());
+            }
+            Message::StartMonitoring => {
+                self.monitoring = true;
+                self.push_line(LineKind::Info, "=== Мониторинг запущен ===".to_string());
+            }
+            Message::StopMonitoring => {
+                self.monitoring = false;
+                self.push_line(LineKind::Info, "=== Мониторинг остановлен ===".to_string());
+            }
+            Message::ToggleErrorPanel => {
+                self.show_errors = !self.show_errors;
+            }
+            Message::ErrorFilterChanged(filter) => {
+                self.error_filter = filter;
+            }
+            Message::ClearErrors => {
+                self.errors.clear();
+            }
+            Message::SearchChanged(query) => {
+                self.search_query = query;
+                self.search_match = 0;
+                return self.scroll_to_match();
+            }
+            Message::SearchNext => {
+                let count = self.search_matches().len();
+                if count > 0 {
+                    self.search_match = (self.search_match + 1) % count;
+                }
+                return self.scroll_to_match();
+            }
+            Message::SearchPrev => {
+                let count = self.search_matches().len();
+                if count > 0 {
+                    self.search_match = (self.search_match + count - 1) % count;
+                }
+                return self.scroll_to_match();
+            }
+            Message::CancelClear => {
+                self.clear_armed = false;
+            }
+            Message::UndoClear => {
+                if let Some(backup) = self.clear_backup.take().filter(ClearBackup::is_fresh) {
+                    self.terminal_output = backup.lines;
+                    self.log_written = backup.log_written;
+                    self.last_push = backup.last_push;
+                    self.dirty = backup.dirty;
+                    self.push_line(LineKind::Info, "↩️ Очистка отменена".to_string());
+                }
+            }
+            Message::MarkerChanged(value) => {
+                self.marker_input = value;
+            }
+            Message::InsertMarker => {
+                // Only goes into the log; nothing is sent to the port
+                self.markers_inserted += 1;
+                let note = match self.marker_input.trim() {
+                    "" => format!("метка {}", self.markers_inserted),
+                    note => note.to_string(),
+                };
+                self.push_line(LineKind::Info, format!("--- {} ---", note));
+                self.marker_input.clear();
+            }
+            Message::Tick => {
+                self.activity.update(self.received_bytes, self.sent_bytes);
+                // The transfer thread owns the port until it finishes
+                if self.file_transfer.is_some() {
+                    return Task::none();
+                }
+                // Reading happens on the port's own thread; the tick only samples the driver
+                if let Some(control) = self.serial_port.as_ref().and_then(PortIo::try_control) {
+                    self.driver_queue = control.bytes_to_read().ok();
+                }
+                self.advance_script();
+                self.advance_ping(Instant::now());
+                if std::mem::take(&mut self.alert_pending) {
+                    return self.raise_alert();
+                }
+            }
+            Message::ColorInputChanged(kind, value) => {
+                *self.color_inputs.get_mut(kind) = value;
+            }
+            Message::MaxLineLenChanged(value) => {
+                self.max_line_len_input = value;
+            }
+            Message::ApplyMaxLineLen => match parse_max_line_len(&self.max_line_len_input) {
+                Ok(max_len) => {
+                    self.config.max_line_len = max_len;
+                    self.save_config();
+                    self.push_line(
+                        LineKind::Info,
+                        format!("📏 Максимальная длина строки: {}", max_len),
+                    );
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::RxWrapChanged(value) => {
+                self.rx_wrap_input = value;
+            }
+            Message::ApplyRxWrap => match parse_rx_wrap(&self.rx_wrap_input) {
+                Ok(width) => {
+                    self.config.rx_wrap = width;
+                    self.save_config();
+                    self.push_line(
+                        LineKind::Info,
+                        if width == 0 {
+                            "↩️ Перенос принятых данных выключен".to_string()
+                        } else {
+                            format!("↩️ Перенос принятых данных каждые {} символов", width)
+                        },
+                    );
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::PromptChanged(value) => {
+                self.prompt_input = value;
+            }
+            Message::ApplyPrompt => {
+                self.config.prompt = self.prompt_input.clone();
+                self.save_config();
+            }
+            Message::PromptModeSelected(mode) => {
+                self.config.prompt_mode = mode;
+                self.save_config();
+            }
+            Message::RxFilterSelected(filter) => {
+                self.config.rx_filter = filter;
+                self.save_config();
+            }
+            Message::NulHandlingSelected(handling) => {
+                self.config.nul_handling = handling;
+                self.save_config();
+            }
+            Message::RxFilterRangeChanged(value) => {
+                self.rx_filter_range_input = value;
+            }
+            Message::ApplyRxFilterRange => match parse_byte_range(&self.rx_filter_range_input) {
+                Ok(range) => {
+                    self.config.rx_filter_range = range;
+                    self.rx_filter_range_input = byte_range_label(range);
+                    self.save_config();
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::AlertPatternChanged(value) => {
+                self.alert_pattern_input = value;
+            }
+            Message::ApplyAlertPattern => {
+                self.config.alert_pattern = self.alert_pattern_input.clone();
+                self.save_config();
+                self.push_line(
+                    LineKind::Info,
+                    if self.config.alert_pattern.is_empty() {
+                        "🔔 Тревога выключена".to_string()
+                    } else {
+                        format!("🔔 Тревога при «{}»", self.config.alert_pattern)
+                    },
+                );
+            }
+            Message::AlertSoundToggled(enabled) => {
+                self.config.alert_sound = enabled;
+                self.save_config();
+            }
+            Message::AlertFlashToggled(enabled) => {
+                self.config.alert_flash = enabled;
+                self.save_config();
+            }
+            Message::FrameDelimiterChanged(value) => {
+                self.frame_delimiter_input = value;
+            }
+            Message::ApplyFrameDelimiter => {
+                match parse_frame_delimiter(&self.frame_delimiter_input) {
+                    Ok(delimiter) => {
+                        // A partial frame cut with the old delimiter is shown as is
+                        self.flush_frame_buffer();
+                        self.config.frame_delimiter = delimiter;
+                        self.frame_delimiter_input = delimiter_label(delimiter);
+                        self.save_config();
+                        self.push_line(
+                            LineKind::Info,
+                            format!("✂️ Разделитель кадров: {}", delimiter_label(delimiter)),
+                        );
+                    }
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+                }
+            }
+            Message::IdleTimeoutChanged(value) => {
+                self.idle_timeout_input = value;
+            }
+            Message::LargeFileChanged(value) => {
+                self.large_file_input = value;
+            }
+            Message::ApplyLargeFile => match parse_large_file_kb(&self.large_file_input) {
+                Ok(kb) => {
+                    self.config.large_file_kb = kb;
+                    self.save_config();
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::ApplyIdleTimeout => match parse_idle_timeout(&self.idle_timeout_input) {
+                Ok(minutes) => {
+                    self.config.idle_timeout_min = minutes;
+                    self.save_config();
+                    self.push_line(
+                        LineKind::Info,
+                        if minutes == 0 {
+                            "⏲️ Автоотключение выключено".to_string()
+                        } else {
+                            format!("⏲️ Автоотключение после {} мин простоя", minutes)
+                        },
+                    );
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::TagPortsToggled(enabled) => {
+                self.config.tag_ports = enabled;
+                self.save_config();
+            }
+            Message::PortColorChanged(value) => {
+                self.port_color_input = value;
+            }
+            Message::ApplyPortColor => {
+                let Some(port) = self.port_settings.port_name.clone() else {
+                    return Task::none();
+                };
+                if self.port_color_input.trim().is_empty() {
+                    // Back to the palette color
+                    self.config.port_colors.remove(&port);
+                    self.save_config();
+                } else {
+                    match parse_hex_color(&self.port_color_input) {
+                        Ok(_) => {
+                            self.config
+                                .port_colors
+                                .insert(port, self.port_color_input.trim().to_string());
+                            self.save_config();
+                        }
+                        Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+                    }
+                }
+            }
+            Message::ApplyColors => {
+                let invalid: Vec<String> = LineKind::ALL
+                    .into_iter()
+                    .filter_map(|kind| {
+                        parse_hex_color(self.color_inputs.get(kind))
+                            .err()
+                            .map(|e| format!("{}: {}", kind, e))
+                    })
+                    .collect();
+                if invalid.is_empty() {
+                    self.config.line_colors = self.color_inputs.clone();
+                    self.save_config();
+                    self.push_line(LineKind::Info, "🎨 Цвета терминала обновлены".to_string());
+                } else {
+                    self.push_line(
+                        LineKind::Error,
+                        format!("❌ Неверный цвет: {}", invalid.join("; ")),
+                    );
+                }
+            }
+            Message::ResetColors => {
+                self.config.line_colors = LineColors::for_theme(&self.current_theme());
+                self.color_inputs = self.config.line_colors.clone();
+                self.save_config();
+            }
+            Message::ClockTick => {
+                self.sample_traffic();
+                self.check_idle_timeout();
+                let secs = self.config.autosave_secs;
+                if secs > 0
+                    && self
+                        .last_flush
+                        .is_some_and(|t| t.elapsed() >= Duration::from_secs(secs))
+                {
+                    self.flush_log();
+                }
+            }
+            Message::SaveChart => {
+                let samples: Vec<(u64, u64)> = self.traffic.iter().copied().collect();
+                return Task::perform(async move { save_chart(samples) }, Message::ChartSaved);
+            }
+            Message::ChartSaved(Ok(Some(path))) => {
+                self.push_line(LineKind::Info, format!("💾 График сохранен: {}", path));
+            }
+            Message::ChartSaved(Ok(None)) => {}
+            Message::ChartSaved(Err(e)) => {
+                self.push_line(
+                    LineKind::Error,
+                    format!("❌ Не удалось сохранить график: {}", e),
+                );
+            }
+            Message::StopAll => {
+                self.stop_all_activity();
+            }
+        }
+        Task::none()
+    }
+
+    fn check_idle_timeout(&mut self) {
+        let minutes = self.config.idle_timeout_min;
+        let Some(last) = self.last_activity else {
+            return;
+        };
+        // A running transfer counts as activity even though it bypasses write_bytes
+        if !self.port_settings.connected
+            || self.file_transfer.is_some()
+            || minutes == 0
+            || last.elapsed() < Duration::from_secs(minutes * 60)
+        {
+            return;
+        }
+        if let Some(port_name) = self.port_settings.port_name.clone() {
+            self.close_port();
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "⏏️ {} автоматически отключен после {} мин простоя",
+                    port_name, minutes
+                ),
+            );
+        }
+    }
+
+    fn sample_traffic(&mut self) {
+        let (rx, tx) = self.traffic_counts;
+        self.traffic.push_back((
+            self.received_bytes.saturating_sub(rx),
+            self.sent_bytes.saturating_sub(tx),
+        ));
+        if self.traffic.len() > TRAFFIC_HISTORY_LEN {
+            self.traffic.pop_front();
+        }
+        self.traffic_counts = (self.received_bytes, self.sent_bytes);
+        let (chars, lines) = self.text_counts;
+        self.text_rates = (
+            self.received_chars.saturating_sub(chars),
+            self.received_lines.saturating_sub(lines),
+        );
+        self.text_counts = (self.received_chars, self.received_lines);
+        let samples: Vec<(u64, u64)> = self.traffic.iter().copied().collect();
+        self.traffic_svg = traffic_chart::to_svg(&samples)
+            .ok()
+            .map(|svg| svg::Handle::from_memory(svg.into_bytes()));
+    }
+
+    fn sample_values(&mut self, data: &str) {
+        let mut sampled = false;
+        for value in data.lines().filter_map(first_number) {
+            self.value_samples.push_back(value);
+            if self.value_samples.len() > SPARKLINE_LEN {
+                self.value_samples.pop_front();
+            }
+            sampled = true;
+        }
+        if !sampled {
+            return;
+        }
+        let values: Vec<f64> = self.value_samples.iter().copied().collect();
+        self.sparkline_svg = traffic_chart::sparkline_svg(&values)
+            .ok()
+            .map(|svg| svg::Handle::from_memory(svg.into_bytes()));
+    }
+
+    fn stop_repeat(&mut self) {
+        if self.repeat_running {
+            self.repeat_running = false;
+            self.push_line(
+                LineKind::Info,
+                "⏹ Циклическая отправка остановлена".to_string(),
+            );
+        }
+    }
+
+    // Indices of terminal lines containing the search text, case-insensitive
+    fn search_matches(&self) -> Vec<usize> {
+        let query = self.search_query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        self.terminal_output
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.text.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Changes whenever a line is added, extended or has its repeat counter bumped
+    fn output_marker(&self) -> (usize, usize, u32) {
+        self.terminal_output.back().map_or((0, 0, 0), |line| {
+            (self.terminal_output.len(), line.text.len(), line.repeats)
+        })
+    }
+
+    fn snap_to_bottom(&self) -> Task<Message> {
+        scrollable::snap_to(
+            scrollable::Id::new(TERMINAL_SCROLL_ID),
+            scrollable::RelativeOffset::END,
+        )
+    }
+
+    // Lines are close to uniform height, so a relative offset is accurate enough
+    fn scroll_to_match(&self) -> Task<Message> {
+        let Some(&line) = self.search_matches().get(self.search_match) else {
+            return Task::none();
+        };
+        let last = self.terminal_output.len().saturating_sub(1).max(1);
+        scrollable::snap_to(
+            scrollable::Id::new(TERMINAL_SCROLL_ID),
+            scrollable::RelativeOffset {
+                x: 0.0,
+                y: line as f32 / last as f32,
             },
-            Task::none(),
         )
     }
 
-    pub fn update(&mut self, _message: Message) -> Task<Message> {
-        // TODO: implement update logic
-        Task::none()
+    fn stop_line_queue(&mut self, reason: &str) {
+        if let Some(queue) = self.line_queue.take() {
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "📨 Построчная отправка {} после {} из {} строк",
+                    reason,
+                    queue.index,
+                    queue.payloads.len()
+                ),
+            );
+        }
+    }
+
+    // Halts transfers and queued output but keeps the port open
+    fn stop_all_activity(&mut self) {
+        self.cancel_flag.store(true, Ordering::Relaxed);
+        self.send_recovery = None;
+        self.stop_repeat();
+        self.stop_script("остановлен");
+        self.stop_replay("остановлено");
+        self.stop_line_queue("прервана");
+        // A running transfer thread owns the port and flushes it itself when it sees the flag
+        if self.file_transfer.is_none() {
+            if let Some(port) = &self.serial_port {
+                port.discard_pending();
+                let _ = port.control().clear(ClearBuffer::Output);
+            }
+        }
+        self.push_line(LineKind::Info, "⛔ Вся активность остановлена".to_string());
+    }
+
+    // Single entry point for appending to the terminal log
+    fn push_line(&mut self, kind: LineKind, text: String) {
+        self.push_data_line(kind, text, Vec::new());
+    }
+
+    fn push_data_line(&mut self, kind: LineKind, text: String, bytes: Vec<u8>) {
+        let now = Instant::now();
+        let delta = self
+            .last_push
+            .map_or(Duration::ZERO, |last| now.duration_since(last));
+        self.last_push = Some(now);
+        self.dirty = true;
+        let exchange = match kind {
+            LineKind::Tx => {
+                self.next_exchange += 1;
+                self.open_exchange = Some((self.next_exchange, now));
+                Some(self.next_exchange)
+            }
+            LineKind::Rx => {
+                let window = Duration::from_millis(self.config.exchange_window_ms);
+                self.open_exchange
+                    .filter(|(_, sent)| window.is_zero() || now.duration_since(*sent) <= window)
+                    .map(|(id, _)| id)
+            }
+            _ => None,
+        };
+        if kind == LineKind::Error {
+            self.errors.push_back(ErrorEntry {
+                time: chrono::Local::now().format("%H:%M:%S").to_string(),
+                text: text.clone(),
+            });
+            if self.errors.len() > MAX_ERRORS {
+                self.errors.pop_front();
+            }
+        }
+        self.terminal_output.push_back(TerminalLine {
+            kind,
+            text,
+            repeats: 1,
+            time: chrono::Local::now(),
+            delta,
+            bytes,
+            port: self
+                .port_settings
+                .connected
+                .then(|| self.port_settings.port_name.clone())
+                .flatten(),
+            exchange,
+        });
+    }
+
+    fn port_color(&self, port: &str) -> Color {
+        self.config
+            .port_colors
+            .get(port)
+            .and_then(|value| parse_hex_color(value).ok())
+            .unwrap_or_else(|| {
+                let hash = port
+                    .bytes()
+                    .fold(0usize, |h, b| h.wrapping_mul(31) + b as usize);
+                PORT_PALETTE[hash % PORT_PALETTE.len()]
+            })
+    }
+
+    // Echoes a transmission as a TX line carrying its bytes, then writes it
+    fn send_with_echo(&mut self, echo: String, data: &[u8]) {
+        // No echo for bytes that never leave; write_bytes logs the refusal
+        if self.port_settings.read_only || self.file_transfer.is_some() {
+            self.write_to_port(data);
+            return;
+        }
+        self.push_data_line(LineKind::Tx, format!(">>> {}", echo), data.to_vec());
+        self.write_to_port(data);
+    }
+
+    fn save_config(&mut self) {
+        if let Err(e) = self.config.save() {
+            self.push_line(
+                LineKind::Error,
+                format!("❌ Не удалось сохранить настройки: {}", e),
+            );
+        }
+    }
+
+    // Splits incoming data at the frame delimiter; the unfinished tail waits for the next read
+    fn receive_chunk(&mut self, bytes: &[u8]) {
+        // Counted as read from the port: hooks may resize the data and frames may wait in
+        // the buffer, but neither changes what came over the wire
+        self.received_bytes += bytes.len() as u64;
+        let bytes = &self.apply_hook(false, bytes);
+        self.feed_capture(bytes);
+        let Some(delimiter) = self.config.frame_delimiter else {
+            if self.show_timestamps && self.config.line_timestamps {
+                let (pieces, mid_line) = split_line_pieces(bytes, self.mid_line);
+                self.mid_line = mid_line;
+                for (starts_line, piece) in pieces {
+                    self.handle_received(piece, !starts_line);
+                }
+            } else {
+                self.handle_received(bytes, false);
+            }
+            return;
+        };
+        self.frame_buffer.extend_from_slice(bytes);
+        while let Some(end) = self.frame_buffer.iter().position(|&b| b == delimiter) {
+            let frame: Vec<u8> = self.frame_buffer.drain(..=end).collect();
+            self.handle_received(&frame, false);
+        }
+    }
+
+    fn flush_frame_buffer(&mut self) {
+        if !self.frame_buffer.is_empty() {
+            let frame = std::mem::take(&mut self.frame_buffer);
+            self.handle_received(&frame, false);
+        }
+    }
+
+    // Turns one read chunk or frame into terminal output and feeds scripts, ping and logs.
+    // `continues_line` appends to the last RX line so it keeps its original timestamp
+    fn handle_received(&mut self, bytes: &[u8], continues_line: bool) {
+        // Restoring older lines under newer data would scramble the order
+        self.clear_backup = None;
+        let filtered = filter_bytes(bytes, self.config.rx_filter, self.config.rx_filter_range);
+        let mut data = self
+            .config
+            .nul_handling
+            .apply(String::from_utf8_lossy(&filtered).to_string());
+        let mut overwrite = false;
+        if self.cr_overwrite {
+            let (tail, rewind, pending) = apply_carriage_returns(&data, self.cr_pending);
+            data = tail;
+            overwrite = rewind;
+            self.cr_pending = pending;
+            if data.is_empty() && !overwrite {
+                // A lone CR: nothing to show until the next chunk decides what it means
+                self.last_rx_frame = bytes.to_vec();
+                return;
+            }
+        }
+        if let Some(run) = &mut self.script {
+            run.received.push_str(&data);
+        }
+        if let Some(run) = &mut self.ping {
+            run.received.push_str(&data);
+        }
+        if self.config.value_sparkline {
+            self.sample_values(&data);
+        }
+        let line = format!("<- {}", data);
+        // Identical consecutive readings only bump the counter of the existing line.
+        // Alert lines raised by that line sit after it but must not end it
+        let tail = self
+            .terminal_output
+            .iter()
+            .rposition(|line| line.kind != LineKind::Alert);
+        match tail.and_then(|index| self.terminal_output.get_mut(index)) {
+            Some(last) if overwrite && last.kind == LineKind::Rx => {
+                last.text = line;
+                last.bytes = bytes.to_vec();
+                last.repeats = 1;
+                self.dirty = true;
+            }
+            Some(last) if continues_line && last.kind == LineKind::Rx => {
+                last.text.push_str(&data);
+                last.bytes.extend_from_slice(bytes);
+                self.dirty = true;
+            }
+            Some(last)
+                if self.suppress_repeats && last.kind == LineKind::Rx && last.text == line =>
+            {
+                last.repeats += 1;
+                self.dirty = true;
+            }
+            _ => self.push_data_line(LineKind::Rx, line, bytes.to_vec()),
+        }
+        if !self.config.alert_pattern.is_empty() && data.contains(&self.config.alert_pattern) {
+            self.push_line(LineKind::Alert, format!("🔔 {}", data.trim_end()));
+            self.alert_pending = true;
+        }
+        self.received_chars += data.chars().count() as u64;
+        // With a frame delimiter every call is one complete frame
+        self.received_lines += match self.config.frame_delimiter {
+            Some(_) => 1,
+            None => data.matches('\n').count() as u64,
+        };
+        self.last_rx_frame = bytes.to_vec();
+        self.last_activity = Some(Instant::now());
+
+        self.rx_since_flush += bytes.len() as u64;
+        let kb = self.config.autosave_kb;
+        if kb > 0 && self.rx_since_flush >= kb * 1024 {
+            self.flush_log();
+        }
+    }
+
+    fn tick_interval(&self) -> Duration {
+        if self.focused {
+            self.poll_interval()
+        } else {
+            self.poll_interval().max(BACKGROUND_POLL_INTERVAL)
+        }
+    }
+
+    // Whether the UI should offer anything that writes to the port
+    fn can_transmit(&self) -> bool {
+        self.port_settings.connected && !self.port_settings.read_only
+    }
+
+    // Beeps and/or flashes, at most once per ALERT_MIN_INTERVAL
+    fn raise_alert(&mut self) -> Task<Message> {
+        if self
+            .last_alert
+            .is_some_and(|last| last.elapsed() < ALERT_MIN_INTERVAL)
+        {
+            return Task::none();
+        }
+        self.last_alert = Some(Instant::now());
+        if self.config.alert_sound {
+            system_beep();
+        }
+        if !self.config.alert_flash {
+            return Task::none();
+        }
+        self.flash_until = Some(Instant::now() + ALERT_FLASH);
+        match self.main_window {
+            Some(id) => {
+                window::request_user_attention(id, Some(window::UserAttention::Informational))
+            }
+            None => Task::none(),
+        }
+    }
+
+    // Turns user input into the bytes to transmit plus the text echoed into the log.
+    // Order in both modes: prefix, payload, suffix, checksum, line ending; text mode trims
+    // first when asked. Each line of a multi-line input is framed on its own
+    fn build_payload(&self, input: &str) -> Result<(Vec<u8>, String), String> {
+        if !input.contains('\n') {
+            return self.build_line_payload(input);
+        }
+        let mut bytes = Vec::new();
+        let mut echoes = Vec::new();
+        for line in input.lines() {
+            let (data, echo) = self.build_line_payload(line)?;
+            bytes.extend_from_slice(&data);
+            echoes.push(echo);
+        }
+        Ok((bytes, echoes.join(" ⏎ ")))
+    }
+
+    fn build_line_payload(&self, input: &str) -> Result<(Vec<u8>, String), String> {
+        let mut bytes = parse_escapes(&self.config.send_prefix)?;
+        let suffix = parse_escapes(&self.config.send_suffix)?;
+        // With framing around the text the echo has to show what actually went out
+        let framed = !bytes.is_empty()
+            || !suffix.is_empty()
+            || self.config.send_checksum != SendChecksum::None;
+        let text = if self.hex_mode {
+            bytes.extend(hex::hex_to_bytes(input)?);
+            None
+        } else {
+            let text = if self.trim_on_send {
+                input.trim()
+            } else {
+                input
+            };
+            bytes.extend_from_slice(text.as_bytes());
+            Some(text)
+        };
+        bytes.extend_from_slice(&suffix);
+        self.config.send_checksum.append(&mut bytes);
+        bytes.extend_from_slice(self.line_ending.as_bytes());
+        // Hex input is echoed as the bytes that went out, line ending included
+        let echo = match text {
+            Some(text) if !framed => text.to_string(),
+            _ => hex::bytes_to_hex(&bytes),
+        };
+        Ok((bytes, echo))
+    }
+
+    // Confirmed in the log once the writer thread has put it on the wire
+    fn write_to_port(&mut self, data: &[u8]) {
+        self.write_bytes(data, WriteKind::Confirmed);
+    }
+
+    // Hands the data to the writer thread; the outcome comes back as a port event.
+    // A running transfer writes through its own handle, so anything queued here would
+    // land in the middle of the file
+    fn write_bytes(&mut self, data: &[u8], kind: WriteKind) {
+        if self.port_settings.read_only {
+            self.push_line(
+                LineKind::Error,
+                "❌ Порт открыт только для чтения, данные не отправлены".to_string(),
+            );
+            return;
+        }
+        if self.refuse_during_transfer() {
+            return;
+        }
+        let data = self.apply_hook(true, data);
+        if let Some((started, recording)) = &mut self.tx_recording {
+            recording.push(started.elapsed(), &data);
+        }
+        self.last_activity = Some(Instant::now());
+        self.queue_write(data, kind);
+    }
+
+    fn queue_write(&mut self, data: Vec<u8>, kind: WriteKind) {
+        let len = data.len();
+        if self
+            .serial_port
+            .as_ref()
+            .is_some_and(|port| port.send(WriteJob::new(data, kind)))
+        {
+            self.tx_queued += 1;
+            self.tx_queued_bytes += len;
+        }
+    }
+
+    // Re-checks the input field so hex mode can flag malformed input before sending
+    fn validate_input(&mut self) {
+        self.hex_error = if self.hex_mode {
+            hex::hex_to_bytes(&self.input_text).err()
+        } else {
+            None
+        };
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            window::close_events().map(Message::WindowClosed),
+            iced::event::listen_with(|event, _, _| match event {
+                iced::Event::Window(window::Event::Focused) => Some(Message::FocusChanged(true)),
+                iced::Event::Window(window::Event::Unfocused) => Some(Message::FocusChanged(false)),
+                _ => None,
+            }),
+        ];
+        if self.port_settings.connected {
+            subscriptions.push(iced::time::every(self.tick_interval()).map(|_| Message::Tick));
+            subscriptions
+                .push(iced::time::every(Duration::from_secs(1)).map(|_| Message::ClockTick));
+        }
+        if self.port_settings.connected
+            && self.raw_mode
+            && matches!(self.current_window, WindowState::Terminal)
+        {
+            subscriptions.push(keyboard::on_key_press(|key, modifiers| {
+                Some(Message::RawKey(key, modifiers))
+            }));
+        }
+        if !self.raw_mode && matches!(self.current_window, WindowState::Terminal) {
+            // Ctrl+M drops a marker without leaving the command field
+            subscriptions.push(keyboard::on_key_press(|key, modifiers| {
+                (modifiers.command() && key.as_ref() == Key::Character("m"))
+                    .then_some(Message::InsertMarker)
+            }));
+        }
+        if self.port_settings.connected && self.repeat_running {
+            if let Ok(interval) = parse_interval(&self.repeat_interval_input) {
+                subscriptions.push(iced::time::every(interval).map(|_| Message::RepeatTick));
+            }
+        }
+        if self.send_recovery.is_some() {
+            let delay = Duration::from_millis(self.config.connect_retry_delay_ms.max(1));
+            subscriptions.push(iced::time::every(delay).map(|_| Message::RecoverSend));
+        }
+        // The pinned device is only noticed by polling the port list
+        let watch_pinned = self.config.pinned_usb_enabled && self.config.pinned_usb.is_some();
+        if self.auto_refresh_ports || watch_pinned {
+            subscriptions
+                .push(iced::time::every(Duration::from_secs(2)).map(|_| Message::PollPorts));
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    fn title(&self, window: window::Id) -> String {
+        if self.log_window == Some(window) {
+            return "COM Terminal - Лог".to_string();
+        }
+        let status = if self.port_settings.connected {
+            format!(
+                " - Подключен к {}",
+                self.port_settings
+                    .port_name
+                    .as_ref()
+                    .unwrap_or(&"Unknown".to_string())
+            )
+        } else {
+            " - Отключен".to_string()
+        };
+
+        match self.current_window {
+            WindowState::Terminal => format!("COM Terminal - Терминал{}", status),
+            WindowState::Settings => format!("COM Terminal - Настройки{}", status),
+            WindowState::Monitor => format!("COM Terminal - Мониторинг{}", status),
+            WindowState::FileView => format!("COM Terminal - Файлы{}", status),
+            WindowState::Compare => format!("COM Terminal - Сравнение логов{}", status),
+        }
+    }
+
+    // Every window shares one theme
+    fn theme(&self, _window: window::Id) -> Theme {
+        self.current_theme()
+    }
+
+    fn current_theme(&self) -> Theme {
+        Theme::Dark
+    }
+}
+
+// The OS notification sound. com_term has no console on Windows, so a BEL would go nowhere
+#[cfg(windows)]
+fn system_beep() {
+    #[link(name = "user32")]
+    extern "system" {
+        fn MessageBeep(kind: u32) -> i32;
+    }
+    // MB_OK: the default system sound
+    // SAFETY: MessageBeep takes a plain flag and only queues the sound
+    unsafe {
+        MessageBeep(0);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_beep() {
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        fn NSBeep();
+    }
+    // SAFETY: NSBeep takes nothing and only plays the alert sound
+    unsafe {
+        NSBeep();
+    }
+}
+
+// No system sound without an audio library; the bell of the terminal the app was started
+// from is the closest thing, and silent when there is none
+#[cfg(not(any(windows, target_os = "macos")))]
+fn system_beep() {
+    if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        let _ = tty.write_all(b"\x07");
+    }
+}
+
+fn parse_baud(value: &str) -> Result<u32, String> {
+    match value.trim().parse::<u32>() {
+        Ok(rate) if rate > 0 => Ok(rate),
+        _ => Err(format!("'{}' не является скоростью в baud", value.trim())),
+    }
+}
+
+// Accepts "1A2B3C4D" or "0x1A2B3C4D", any case
+fn parse_crc(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u32::from_str_radix(digits, 16).ok()
+}
+
+const INSPECTOR_MAX_BYTES: usize = 64;
+
+// Renders bytes as aligned HEX / DEC / ASCII rows, 8 bytes per row, up to `limit` bytes
+fn inspect_bytes(bytes: &[u8], limit: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (row, chunk) in bytes[..bytes.len().min(limit)].chunks(8).enumerate() {
+        let hex: String = chunk.iter().map(|b| format!(" {:>3X}", b)).collect();
+        let dec: String = chunk.iter().map(|b| format!(" {:>3}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                let c = if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                };
+                format!(" {:>3}", c)
+            })
+            .collect();
+        lines.push(format!("{:04} HEX{}", row * 8, hex));
+        lines.push(format!("     DEC{}", dec));
+        lines.push(format!("     ASC{}", ascii));
+    }
+    if bytes.len() > limit {
+        lines.push(format!("… еще {} байт не показано", bytes.len() - limit));
+    }
+    lines
+}
+
+const MAX_FILL_COUNT: usize = 1 << 20;
+
+// Bytes a keystroke produces in raw mode; Ctrl+letter maps to the matching control code
+fn raw_key_bytes(key: &Key, modifiers: Modifiers, line_ending: LineEnding) -> Option<Vec<u8>> {
+    match key {
+        Key::Named(key::Named::Enter) => Some(match line_ending {
+            // Without a configured ending Enter still has to send something
+            LineEnding::None => b"\r".to_vec(),
+            ending => ending.as_bytes().to_vec(),
+        }),
+        Key::Named(key::Named::Backspace) => Some(vec![0x08]),
+        Key::Named(key::Named::Tab) => Some(vec![b'\t']),
+        Key::Named(key::Named::Escape) => Some(vec![0x1B]),
+        Key::Named(key::Named::Space) => Some(vec![b' ']),
+        Key::Character(c) if modifiers.control() => {
+            let c = c.chars().next()?.to_ascii_uppercase();
+            c.is_ascii_uppercase().then(|| vec![c as u8 - b'A' + 1])
+        }
+        Key::Character(c) => Some(c.as_bytes().to_vec()),
+        _ => None,
+    }
+}
+const MIN_REPEAT_INTERVAL_MS: u64 = 50;
+
+// Splits "cmd1; cmd2" into trimmed, non-empty commands
+fn parse_command_list(input: &str) -> Vec<String> {
+    input
+        .split(';')
+        .map(str::trim)
+        .filter(|cmd| !cmd.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let ms = value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' не является интервалом в мс", value.trim()))?;
+    if ms < MIN_REPEAT_INTERVAL_MS {
+        return Err(format!("интервал не меньше {} мс", MIN_REPEAT_INTERVAL_MS));
+    }
+    Ok(Duration::from_millis(ms))
+}
+
+// Accepts decimal ("85"), hex ("0x55") or binary ("0b1010") byte values
+fn parse_byte(value: &str) -> Result<u8, String> {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+    let parsed = if let Some(hex) = lower.strip_prefix("0x") {
+        u8::from_str_radix(hex, 16)
+    } else if let Some(bin) = lower.strip_prefix("0b") {
+        u8::from_str_radix(bin, 2)
+    } else {
+        value.parse::<u8>()
+    };
+    parsed.map_err(|_| format!("'{}' не является байтом (0..255, 0x00..0xFF, 0b...)", value))
+}
+
+// Space- or comma-separated byte tokens; every bad token is reported with its position
+fn parse_byte_list(input: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut errors = Vec::new();
+    let tokens = input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty());
+    for (index, token) in tokens.enumerate() {
+        match parse_byte(token) {
+            Ok(byte) => bytes.push(byte),
+            Err(e) => errors.push(format!("#{}: {}", index + 1, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    if bytes.is_empty() {
+        return Err("нет байтов для отправки".to_string());
+    }
+    Ok(bytes)
+}
+
+fn parse_capture_size(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(size) if (1..=MAX_CAPTURE_BYTES).contains(&size) => Ok(size),
+        _ => Err(format!(
+            "'{}': размер захвата должен быть от 1 до {}",
+            value.trim(),
+            MAX_CAPTURE_BYTES
+        )),
+    }
+}
+
+fn parse_line_delay(value: &str) -> Result<Duration, String> {
+    match value.trim().parse::<u64>() {
+        Ok(ms) if ms <= 60_000 => Ok(Duration::from_millis(ms)),
+        _ => Err(format!(
+            "'{}': пауза между строками от 0 до 60000 мс",
+            value.trim()
+        )),
+    }
+}
+
+fn parse_replay_speed(value: &str) -> Result<f64, String> {
+    match value.trim().replace(',', ".").parse::<f64>() {
+        Ok(speed) if (0.1..=100.0).contains(&speed) => Ok(speed),
+        _ => Err(format!(
+            "'{}': скорость воспроизведения от 0.1 до 100",
+            value.trim()
+        )),
+    }
+}
+
+fn parse_fill(byte: &str, count: &str) -> Result<(u8, usize), String> {
+    let byte = parse_byte(byte)?;
+    let count = count
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("'{}' не является количеством", count.trim()))?;
+    if count == 0 || count > MAX_FILL_COUNT {
+        return Err(format!("количество должно быть от 1 до {}", MAX_FILL_COUNT));
+    }
+    Ok((byte, count))
+}
+
+const DEFAULT_RESET_PULSE_MS: u64 = 50;
+
+fn parse_reset_pulse(value: &str) -> Result<Duration, String> {
+    match value.trim().parse::<u64>() {
+        Ok(ms) if (1..=5000).contains(&ms) => Ok(Duration::from_millis(ms)),
+        _ => Err(format!(
+            "'{}': длительность импульса должна быть от 1 до 5000 мс",
+            value.trim()
+        )),
+    }
+}
+
+fn parse_autosave(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' не является числом", value.trim()))
+}
+
+fn parse_connect_retry(retries: &str, delay: &str) -> Result<(u32, u64), String> {
+    let retries = match retries.trim().parse::<u32>() {
+        Ok(n) if n <= 20 => n,
+        _ => {
+            return Err(format!(
+                "'{}': повторов должно быть от 0 до 20",
+                retries.trim()
+            ))
+        }
+    };
+    let delay = delay
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' не является паузой в мс", delay.trim()))?;
+    Ok((retries, delay))
+}
+
+fn parse_poll_interval(value: &str) -> Result<u64, String> {
+    match value.trim().parse::<u64>() {
+        Ok(ms) if (1..=1000).contains(&ms) => Ok(ms),
+        _ => Err(format!(
+            "'{}': интервал опроса должен быть от 1 до 1000 мс",
+            value.trim()
+        )),
+    }
+}
+
+// Hex "VID:PID" as shown by lsusb / Device Manager; empty clears the pin
+fn parse_vid_pid(value: &str) -> Result<Option<(u16, u16)>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let error = || format!("'{}' не является VID:PID (например 0403:6001)", value);
+    let (vid, pid) = value.split_once(':').ok_or_else(error)?;
+    let vid = u16::from_str_radix(vid.trim(), 16).map_err(|_| error())?;
+    let pid = u16::from_str_radix(pid.trim(), 16).map_err(|_| error())?;
+    Ok(Some((vid, pid)))
+}
+
+fn vid_pid_label((vid, pid): (u16, u16)) -> String {
+    format!("{:04X}:{:04X}", vid, pid)
+}
+
+// Text with C-style escapes: \r \n \t \0 \\ and \xNN
+fn parse_escapes(value: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('r') => bytes.push(b'\r'),
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 2)
+                    .ok_or_else(|| format!("'\\x{}': нужны две hex-цифры", hex))?;
+                bytes.push(byte);
+            }
+            Some(other) => return Err(format!("неизвестная последовательность '\\{}'", other)),
+            None => return Err("'\\' в конце строки".to_string()),
+        }
+    }
+    Ok(bytes)
+}
+
+// "none" (or empty) disables splitting, anything else must be a single byte
+fn parse_frame_delimiter(value: &str) -> Result<Option<u8>, String> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    parse_byte(value).map(Some)
+}
+
+fn delimiter_label(delimiter: Option<u8>) -> String {
+    match delimiter {
+        Some(byte) => format!("0x{:02X}", byte),
+        None => "none".to_string(),
+    }
+}
+
+fn parse_idle_timeout(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' не является числом минут", value.trim()))
+}
+
+fn parse_large_file_kb(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' не является размером в КБ", value.trim()))
+}
+
+fn parse_max_line_len(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(len) if len >= 16 => Ok(len),
+        _ => Err(format!("'{}': нужно число не меньше 16", value.trim())),
+    }
+}
+
+// "0x20-0x7E"; either end may be written in any form parse_byte accepts
+fn parse_byte_range(value: &str) -> Result<(u8, u8), String> {
+    let (low, high) = value
+        .split_once('-')
+        .ok_or_else(|| format!("'{}': нужен диапазон вида 0x20-0x7E", value.trim()))?;
+    let (low, high) = (parse_byte(low)?, parse_byte(high)?);
+    if low > high {
+        return Err(format!("0x{:02X} больше 0x{:02X}", low, high));
+    }
+    Ok((low, high))
+}
+
+fn byte_range_label((low, high): (u8, u8)) -> String {
+    format!("0x{:02X}-0x{:02X}", low, high)
+}
+
+fn parse_exchange_window(value: &str) -> Result<u64, String> {
+    value.trim().parse::<u64>().map_err(|_| {
+        format!(
+            "'{}': нужно время в мс (0 = до следующей отправки)",
+            value.trim()
+        )
+    })
+}
+
+fn parse_rx_wrap(value: &str) -> Result<usize, String> {
+    value
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("'{}': нужно число символов (0 = выкл)", value.trim()))
+}
+
+pub fn run() -> iced::Result {
+    // A daemon rather than an application so the detached log can be a second window
+    iced::daemon(ComTerminal::title, ComTerminal::update, ComTerminal::view)
+        .theme(ComTerminal::theme)
+        .subscription(ComTerminal::subscription)
+        .run_with(|| {
+            let mut terminal = ComTerminal::new();
+            let (id, open) = window::open(window::Settings::default());
+            terminal.main_window = Some(id);
+            let font = iced::font::load(TERMINAL_FONT_BYTES).map(Message::FontLoaded);
+            // Auto-connect may already have opened the port
+            let events = terminal.take_port_events().unwrap_or_else(Task::none);
+            (
+                terminal,
+                Task::batch([open.map(Message::WindowOpened), font, events]),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_cover_every_sequence() {
+        assert_eq!(parse_escapes(""), Ok(Vec::new()));
+        assert_eq!(
+            parse_escapes(r"AT\r\n\t\0\\\x1b\xFF"),
+            Ok(b"AT\r\n\t\0\\\x1b\xff".to_vec())
+        );
+        // Non-ASCII text passes through as UTF-8
+        assert_eq!(parse_escapes("é"), Ok("é".as_bytes().to_vec()));
+    }
+
+    #[test]
+    fn escape_errors_name_the_bad_sequence() {
+        assert_eq!(
+            parse_escapes(r"\x4"),
+            Err(r"'\x4': нужны две hex-цифры".to_string())
+        );
+        assert_eq!(
+            parse_escapes(r"\x"),
+            Err(r"'\x': нужны две hex-цифры".to_string())
+        );
+        assert_eq!(
+            parse_escapes(r"\xZZ"),
+            Err(r"'\xZZ': нужны две hex-цифры".to_string())
+        );
+        assert_eq!(
+            parse_escapes(r"\q"),
+            Err(r"неизвестная последовательность '\q'".to_string())
+        );
+        assert_eq!(
+            parse_escapes("abc\\"),
+            Err(r"'\' в конце строки".to_string())
+        );
+    }
+
+    #[test]
+    fn vid_pid_is_hex_and_optional() {
+        assert_eq!(parse_vid_pid(""), Ok(None));
+        assert_eq!(parse_vid_pid("  "), Ok(None));
+        assert_eq!(parse_vid_pid("0403:6001"), Ok(Some((0x0403, 0x6001))));
+        assert_eq!(parse_vid_pid(" 10c4 : ea60 "), Ok(Some((0x10C4, 0xEA60))));
+        let error = Err("'0403' не является VID:PID (например 0403:6001)".to_string());
+        assert_eq!(parse_vid_pid("0403"), error);
+        assert!(parse_vid_pid("0403:xyz").is_err());
+        assert!(parse_vid_pid("10000:0001").is_err());
+        assert_eq!(vid_pid_label((0x403, 0x6001)), "0403:6001");
+    }
+
+    #[test]
+    fn frame_delimiter_accepts_none_and_single_bytes() {
+        assert_eq!(parse_frame_delimiter(""), Ok(None));
+        assert_eq!(parse_frame_delimiter("NONE"), Ok(None));
+        assert_eq!(parse_frame_delimiter("10"), Ok(Some(b'\n')));
+        assert_eq!(parse_frame_delimiter("0x0D"), Ok(Some(b'\r')));
+        assert_eq!(parse_frame_delimiter("0b11"), Ok(Some(3)));
+        assert_eq!(
+            parse_frame_delimiter("256"),
+            Err("'256' не является байтом (0..255, 0x00..0xFF, 0b...)".to_string())
+        );
+        assert_eq!(delimiter_label(Some(b'\n')), "0x0A");
+        assert_eq!(delimiter_label(None), "none");
+    }
+
+    #[test]
+    fn byte_lists_report_every_bad_token() {
+        assert_eq!(
+            parse_byte_list("255 0xFF, 0b1010,1"),
+            Ok(vec![255, 255, 10, 1])
+        );
+        assert_eq!(
+            parse_byte_list("1 300 2 0xG"),
+            Err("#2: '300' не является байтом (0..255, 0x00..0xFF, 0b...); \
+                 #4: '0xG' не является байтом (0..255, 0x00..0xFF, 0b...)"
+                .to_string())
+        );
+        assert_eq!(
+            parse_byte_list(" , "),
+            Err("нет байтов для отправки".to_string())
+        );
+    }
+
+    #[test]
+    fn crc_accepts_an_optional_prefix() {
+        assert_eq!(parse_crc("1a2b3c4d"), Some(0x1A2B_3C4D));
+        assert_eq!(parse_crc(" 0X1A2B3C4D "), Some(0x1A2B_3C4D));
+        assert_eq!(parse_crc(""), None);
+        assert_eq!(parse_crc("0x"), None);
+        assert_eq!(parse_crc("123456789"), None);
     }
 
-    pub fn view(&self) -> Element<Message> {
-        // delegate to ui module
-        crate::ui::view(self)
+    #[test]
+    fn raw_keys_map_to_terminal_bytes() {
+        let none = Modifiers::empty();
+        let enter = Key::Named(key::Named::Enter);
+        assert_eq!(
+            raw_key_bytes(&enter, none, LineEnding::None),
+            Some(b"\r".to_vec())
+        );
+        assert_eq!(
+            raw_key_bytes(&enter, none, LineEnding::CrLf),
+            Some(b"\r\n".to_vec())
+        );
+        assert_eq!(
+            raw_key_bytes(&Key::Named(key::Named::Backspace), none, LineEnding::None),
+            Some(vec![0x08])
+        );
+        let c = Key::Character("c".into());
+        assert_eq!(
+            raw_key_bytes(&c, none, LineEnding::None),
+            Some(b"c".to_vec())
+        );
+        assert_eq!(
+            raw_key_bytes(&c, Modifiers::CTRL, LineEnding::None),
+            Some(vec![0x03])
+        );
+        // Ctrl only makes control codes out of letters
+        assert_eq!(
+            raw_key_bytes(
+                &Key::Character("1".into()),
+                Modifiers::CTRL,
+                LineEnding::None
+            ),
+            None
+        );
+        assert_eq!(
+            raw_key_bytes(&Key::Named(key::Named::F1), none, LineEnding::None),
+            None
+        );
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
-        // TODO: combine serial subscription + periodic tasks
-        Subscription::none()
+    #[test]
+    fn alert_line_does_not_split_the_rx_line_that_raised_it() {
+        let mut terminal = ComTerminal::with_config(AppConfig {
+            alert_pattern: "ERR".to_string(),
+            frame_delimiter: None,
+            ..AppConfig::default()
+        });
+        terminal.cr_overwrite = false;
+        terminal.handle_received(b"ERR 4", false);
+        terminal.handle_received(b"2\n", true);
+        let tail: Vec<_> = terminal.terminal_output.iter().rev().take(2).collect();
+        assert_eq!(tail[0].kind, LineKind::Alert);
+        assert_eq!(tail[1].kind, LineKind::Rx);
+        assert_eq!(tail[1].text, "<- ERR 42\n");
+        assert_eq!(tail[1].bytes, b"ERR 42\n");
     }
 }
@@ -0,0 +1,335 @@
+use super::*;
+
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Round-trip measurement: send a marker, wait for the reply marker, repeat
+#[derive(Debug, Clone)]
+pub struct PingRun {
+    pub remaining: usize,
+    pub sent_at: Instant,
+    pub received: String,
+    pub results: Vec<Duration>,
+    pub timeouts: usize,
+}
+
+// A loaded SEND/EXPECT script being executed step by step
+#[derive(Debug, Clone)]
+pub struct ScriptRun {
+    pub name: String,
+    pub steps: Vec<script::Step>,
+    pub index: usize,
+    pub step_started: Instant,
+    // Text received since the current step began, searched by EXPECT
+    pub received: String,
+}
+
+impl ComTerminal {
+    pub(super) fn update_automation(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::PingRequestChanged(value) => {
+                self.ping_request_input = value;
+            }
+            Message::PingResponseChanged(value) => {
+                self.ping_response_input = value;
+            }
+            Message::PingCountChanged(value) => {
+                self.ping_count_input = value;
+            }
+            Message::StartPing => {
+                let count = match self.ping_count_input.trim().parse::<usize>() {
+                    Ok(count) if count > 0 => count,
+                    _ => {
+                        self.push_line(
+                            LineKind::Error,
+                            format!("❌ '{}' не является числом попыток", self.ping_count_input),
+                        );
+                        return Task::none();
+                    }
+                };
+                if self.ping_response_input.is_empty() {
+                    self.push_line(
+                        LineKind::Error,
+                        "❌ Ping: не задан ожидаемый ответ".to_string(),
+                    );
+                    return Task::none();
+                }
+                if !self.port_settings.connected {
+                    self.push_line(LineKind::Error, "❌ Ping: порт не открыт".to_string());
+                    return Task::none();
+                }
+                if self.port_settings.read_only {
+                    self.push_line(
+                        LineKind::Error,
+                        "❌ Порт открыт только для чтения".to_string(),
+                    );
+                    return Task::none();
+                }
+                if self.refuse_during_transfer() {
+                    return Task::none();
+                }
+                self.ping = Some(PingRun {
+                    remaining: count,
+                    sent_at: Instant::now(),
+                    received: String::new(),
+                    results: Vec::new(),
+                    timeouts: 0,
+                });
+                self.send_ping();
+            }
+            Message::StopPing => {
+                self.finish_ping();
+            }
+            Message::LoadByteHook => {
+                return Task::perform(async { pick_file_bytes() }, Message::ByteHookLoaded);
+            }
+            #[cfg(feature = "scripting")]
+            Message::ByteHookLoaded(Ok(Some((name, data)))) => {
+                match byte_hook::ByteHook::load(name.clone(), &String::from_utf8_lossy(&data)) {
+                    Ok(hook) => {
+                        self.push_line(LineKind::Info, format!("🧩 Обработчик {} подключен", name));
+                        self.byte_hook = Some(hook);
+                    }
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}: {}", name, e)),
+                }
+            }
+            #[cfg(not(feature = "scripting"))]
+            Message::ByteHookLoaded(Ok(Some(_))) => {
+                self.push_line(
+                    LineKind::Error,
+                    "❌ Программа собрана без поддержки скриптов (--features scripting)"
+                        .to_string(),
+                );
+            }
+            Message::ByteHookLoaded(Ok(None)) => {}
+            Message::ByteHookLoaded(Err(e)) => {
+                self.push_line(LineKind::Error, format!("❌ Ошибка чтения файла: {}", e));
+            }
+            Message::UnloadByteHook =>
+            {
+                #[cfg(feature = "scripting")]
+                if let Some(hook) = self.byte_hook.take() {
+                    self.push_line(
+                        LineKind::Info,
+                        format!("🧩 Обработчик {} отключен", hook.name),
+                    );
+                }
+            }
+            Message::LoadScript => {
+                return Task::perform(async { pick_file_bytes() }, Message::ScriptLoaded);
+            }
+            Message::ScriptLoaded(Ok(Some((name, data)))) => {
+                // A script mostly sends, so it is refused as a whole rather than step by step
+                if self.port_settings.read_only {
+                    self.push_line(
+                        LineKind::Error,
+                        format!("❌ {}: порт открыт только для чтения", name),
+                    );
+                    return Task::none();
+                }
+                if self.refuse_during_transfer() {
+                    return Task::none();
+                }
+                match script::parse(&String::from_utf8_lossy(&data)) {
+                    Ok(steps) if steps.is_empty() => {
+                        self.push_line(LineKind::Error, format!("❌ {}: скрипт пуст", name));
+                    }
+                    Ok(steps) => {
+                        self.push_line(
+                            LineKind::Info,
+                            format!("📜 Скрипт {}: {} шагов", name, steps.len()),
+                        );
+                        self.script = Some(ScriptRun {
+                            name,
+                            steps,
+                            index: 0,
+                            step_started: Instant::now(),
+                            received: String::new(),
+                        });
+                        self.advance_script();
+                    }
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}: {}", name, e)),
+                }
+            }
+            Message::ScriptLoaded(Ok(None)) => {}
+            Message::ScriptLoaded(Err(e)) => {
+                self.push_line(LineKind::Error, format!("❌ Ошибка чтения скрипта: {}", e));
+            }
+            Message::StopScript => {
+                self.stop_script("остановлен");
+            }
+            other => unreachable!("not a automation message: {:?}", other),
+        }
+        Task::none()
+    }
+
+    fn send_ping(&mut self) {
+        let Some(run) = &mut self.ping else {
+            return;
+        };
+        run.remaining -= 1;
+        run.received.clear();
+        match self.build_payload(&self.ping_request_input.clone()) {
+            Ok((data, _)) => {
+                // Timed from just before the write so the write itself is included
+                if let Some(run) = &mut self.ping {
+                    run.sent_at = Instant::now();
+                }
+                self.write_bytes(&data, WriteKind::Quiet);
+            }
+            Err(e) => {
+                self.push_line(LineKind::Error, format!("❌ Ping: {}", e));
+                self.ping = None;
+            }
+        }
+    }
+
+    // Called as data arrives and on every poll tick: records a reply (timed at `now`) or a
+    // timeout and fires the next request
+    pub(super) fn advance_ping(&mut self, now: Instant) {
+        let Some(run) = &mut self.ping else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(run.sent_at);
+        if run.received.contains(&self.ping_response_input) {
+            run.results.push(elapsed);
+        } else if elapsed >= PING_TIMEOUT {
+            run.timeouts += 1;
+        } else {
+            return;
+        }
+        if run.remaining == 0 {
+            self.finish_ping();
+        } else {
+            self.send_ping();
+        }
+    }
+
+    pub(super) fn finish_ping(&mut self) {
+        let Some(run) = self.ping.take() else {
+            return;
+        };
+        let attempts = run.results.len() + run.timeouts;
+        if run.results.is_empty() {
+            self.push_line(
+                LineKind::Error,
+                format!("❌ Ping: нет ответов ({} попыток)", attempts),
+            );
+            return;
+        }
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let min = run.results.iter().copied().min().unwrap_or_default();
+        let max = run.results.iter().copied().max().unwrap_or_default();
+        let avg = run.results.iter().sum::<Duration>() / run.results.len() as u32;
+        self.push_line(
+            LineKind::Info,
+            format!(
+                "📶 Ping: {}/{} ответов, min {:.1} / avg {:.1} / max {:.1} мс",
+                run.results.len(),
+                attempts,
+                ms(min),
+                ms(avg),
+                ms(max)
+            ),
+        );
+    }
+
+    pub(super) fn stop_script(&mut self, reason: &str) {
+        if let Some(run) = self.script.take() {
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "📜 Скрипт {} {} на шаге {}",
+                    run.name,
+                    reason,
+                    run.index + 1
+                ),
+            );
+        }
+    }
+
+    // Runs SEND steps immediately and completes EXPECT steps once their text arrives;
+    // called on every poll tick and right after loading
+    pub(super) fn advance_script(&mut self) {
+        while let Some(run) = &mut self.script {
+            let Some(step) = run.steps.get(run.index).cloned() else {
+                let name = run.name.clone();
+                self.script = None;
+                self.push_line(LineKind::Info, format!("✅ Скрипт {} выполнен", name));
+                return;
+            };
+            let number = run.index + 1;
+            match step {
+                script::Step::Send(command) => {
+                    run.index += 1;
+                    run.step_started = Instant::now();
+                    run.received.clear();
+                    match self.build_payload(&command) {
+                        Ok((data, echo)) => {
+                            self.send_with_echo(format!("[{}] {}", number, echo), &data);
+                        }
+                        Err(e) => {
+                            self.push_line(LineKind::Error, format!("❌ Шаг {}: {}", number, e));
+                            self.script = None;
+                            return;
+                        }
+                    }
+                }
+                script::Step::Expect { pattern, timeout } => {
+                    if run.received.contains(&pattern) {
+                        run.index += 1;
+                        run.step_started = Instant::now();
+                        run.received.clear();
+                        self.push_line(
+                            LineKind::Info,
+                            format!("✓ Шаг {}: получено \"{}\"", number, pattern),
+                        );
+                    } else if run.step_started.elapsed() >= timeout {
+                        let name = run.name.clone();
+                        self.script = None;
+                        self.push_line(
+                            LineKind::Error,
+                            format!(
+                                "❌ Скрипт {}: шаг {} — \"{}\" не получено за {} мс",
+                                name,
+                                number,
+                                pattern,
+                                timeout.as_millis()
+                            ),
+                        );
+                        return;
+                    } else {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    // Passes bytes through the script hook; a failing script is unloaded so traffic keeps flowing.
+    // File transfers run on their own thread and bypass it
+    #[cfg(feature = "scripting")]
+    pub(super) fn apply_hook(&mut self, outgoing: bool, bytes: &[u8]) -> Vec<u8> {
+        let Some(hook) = &self.byte_hook else {
+            return bytes.to_vec();
+        };
+        let result = if outgoing {
+            hook.on_send(bytes)
+        } else {
+            hook.on_recv(bytes)
+        };
+        match result {
+            Ok(Some(rewritten)) => rewritten,
+            Ok(None) => bytes.to_vec(),
+            Err(e) => {
+                self.byte_hook = None;
+                self.push_line(LineKind::Error, format!("❌ Обработчик отключен: {}", e));
+                bytes.to_vec()
+            }
+        }
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    pub(super) fn apply_hook(&mut self, _outgoing: bool, bytes: &[u8]) -> Vec<u8> {
+        bytes.to_vec()
+    }
+}
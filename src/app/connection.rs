@@ -0,0 +1,1110 @@
+use super::*;
+
+// Bookkeeping for the currently open connection
+#[derive(Debug, Clone)]
+pub struct ActiveSession {
+    pub port_name: String,
+    pub baud_rate: u32,
+    pub started_at: Instant,
+    pub started: String,
+    pub rx_at_start: u64,
+    pub tx_at_start: u64,
+}
+
+// Reconnect in progress after a send lost the link; `data` is sent again once if enabled
+pub struct SendRecovery {
+    pub port_name: String,
+    pub data: Vec<u8>,
+    pub attempt: u32,
+}
+
+impl ComTerminal {
+    pub(super) fn update_connection(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::PortSelected(port) => {
+                self.port_settings.port_name = Some(port);
+            }
+            Message::BaudRateSelected(rate) => {
+                self.port_settings.baud_rate = rate;
+            }
+            Message::CustomBaudChanged(value) => {
+                self.custom_baud_input = value;
+            }
+            Message::ApplyCustomBaud => match parse_baud(&self.custom_baud_input) {
+                Ok(rate) => {
+                    self.port_settings.baud_rate = rate;
+                    self.push_line(
+                        LineKind::Info,
+                        format!("⚙️ Скорость {} baud применится при подключении", rate),
+                    );
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::ScanBaud => {
+                return self.start_baud_scan();
+            }
+            Message::BaudScanStep(rate, result) => {
+                if let Some(scan) = &mut self.baud_scan {
+                    scan.results.push((rate, result));
+                }
+            }
+            Message::BaudScanFinished => {
+                if let Some(scan) = &mut self.baud_scan {
+                    scan.running = false;
+                    let message = match scan.best() {
+                        Some(rate) => format!("🔎 Вероятная скорость: {} baud", rate),
+                        None => "🔎 Скорость не определена: устройство молчит".to_string(),
+                    };
+                    self.push_line(LineKind::Info, message);
+                }
+            }
+            Message::ApplyScannedBaud(rate) => {
+                self.baud_scan = None;
+                self.port_settings.baud_rate = rate;
+                return self.update(Message::ConnectPort);
+            }
+            Message::CloseBaudScan => {
+                self.baud_scan = None;
+            }
+            Message::DataBitsSelected(data_bits) => {
+                self.port_settings.data_bits = data_bits;
+                self.note_framing_change();
+            }
+            Message::ParitySelected(parity) => {
+                self.port_settings.parity = parity;
+                self.note_framing_change();
+            }
+            Message::StopBitsSelected(stop_bits) => {
+                self.port_settings.stop_bits = stop_bits;
+                self.note_framing_change();
+            }
+            Message::FlowControlSelected(flow_control) => {
+                self.port_settings.flow_control = flow_control;
+                self.note_framing_change();
+            }
+            Message::ReadOnlyToggled(enabled) => {
+                self.port_settings.read_only = enabled;
+                if enabled {
+                    self.stop_repeat();
+                }
+                if self.port_settings.connected {
+                    self.push_line(
+                        LineKind::Info,
+                        if enabled {
+                            "👁 Только чтение: отправка отключена".to_string()
+                        } else {
+                            "👁 Режим только для чтения выключен".to_string()
+                        },
+                    );
+                }
+            }
+            Message::ExclusiveToggled(enabled) => {
+                self.port_settings.exclusive = enabled;
+                self.note_framing_change();
+            }
+            Message::FramingPreset(data_bits, parity, stop_bits) => {
+                self.port_settings.data_bits = data_bits;
+                self.port_settings.parity = parity;
+                self.port_settings.stop_bits = stop_bits;
+                self.note_framing_change();
+            }
+            Message::ConnectPort => {
+                if let Some(port_name) = self.port_settings.port_name.clone() {
+                    return self.connect_attempt(port_name, 1);
+                }
+            }
+            Message::RetryConnect(port_name, attempt) => {
+                // The user may have connected or picked another port during the pause
+                if !self.port_settings.connected
+                    && self.port_settings.port_name.as_ref() == Some(&port_name)
+                {
+                    return self.connect_attempt(port_name, attempt);
+                }
+            }
+            Message::RecoverSend => {
+                self.recover_send();
+            }
+            Message::ReconnectOnSendErrorToggled(enabled) => {
+                self.config.reconnect_on_send_error = enabled;
+                self.save_config();
+            }
+            Message::ResendAfterReconnectToggled(enabled) => {
+                self.config.resend_after_reconnect = enabled;
+                self.save_config();
+            }
+            Message::DisconnectPort => {
+                if self.send_recovery.take().is_some() {
+                    self.push_line(LineKind::Info, "🔁 Переподключение отменено".to_string());
+                }
+                if let Some(port_name) = self.port_settings.port_name.clone() {
+                    self.close_port();
+                    self.push_line(LineKind::Info, format!("🔌 Отключен от {}", port_name));
+                }
+            }
+            Message::Reopen => {
+                if let (true, Some(port_name)) = (
+                    self.port_settings.connected,
+                    self.port_settings.port_name.clone(),
+                ) {
+                    // Keep the session open across the reopen so it logs one line, not three
+                    let session = self.session.take();
+                    self.close_port();
+                    self.session = session;
+                    match self.open_port(&port_name) {
+                        Ok(()) => {
+                            self.push_line(
+                                LineKind::Info,
+                                format!(
+                                    "🔁 {} переоткрыт с новыми настройками ({} baud)",
+                                    port_name, self.port_settings.baud_rate
+                                ),
+                            );
+                        }
+                        Err(e) => {
+                            self.finish_session();
+                            self.push_open_error(
+                                format!("❌ Ошибка переоткрытия {}", port_name),
+                                &e,
+                            );
+                        }
+                    }
+                }
+            }
+            Message::RefreshPorts => match available_ports() {
+                Ok(ports) => {
+                    self.apply_port_infos(ports);
+                    self.push_line(
+                        LineKind::Info,
+                        format!(
+                            "🔄 Список портов обновлен, найдено: {}",
+                            self.available_ports.len()
+                        ),
+                    );
+                }
+                Err(e) => {
+                    self.push_line(
+                        LineKind::Error,
+                        format!("❌ Ошибка получения списка портов: {}", e),
+                    );
+                }
+            },
+            Message::DumpPorts => {
+                // Straight from the OS, no connection needed: meant to be pasted into bug reports
+                let report = match available_ports() {
+                    Ok(ports) => port_report(&ports),
+                    Err(e) => {
+                        self.push_line(
+                            LineKind::Error,
+                            format!("❌ Ошибка получения списка портов: {}", e),
+                        );
+                        return Task::none();
+                    }
+                };
+                for line in report.lines() {
+                    self.push_line(LineKind::Info, line.to_string());
+                }
+                self.push_line(
+                    LineKind::Info,
+                    "📋 Отчет о портах скопирован в буфер обмена".to_string(),
+                );
+                return iced::clipboard::write(report);
+            }
+            Message::QuickConnect => {
+                if self.port_settings.connected {
+                    return Task::none();
+                }
+                if let Ok(ports) = available_ports() {
+                    self.apply_port_infos(ports);
+                }
+                match self.available_ports.first().cloned() {
+                    Some(first) => {
+                        self.port_settings.port_name = Some(first);
+                        return self.update(Message::ConnectPort);
+                    }
+                    None => {
+                        self.push_line(
+                            LineKind::Info,
+                            "⚠️ Быстрое подключение: COM порты не найдены".to_string(),
+                        );
+                    }
+                }
+            }
+            Message::PollPorts => {
+                // Silent on errors: a failed scan is retried on the next poll anyway
+                if let Ok(ports) = available_ports() {
+                    self.apply_port_infos(ports);
+                }
+            }
+            Message::PortsUpdated(ports) => {
+                self.apply_port_list(ports);
+            }
+            Message::AutoRefreshToggled(enabled) => {
+                self.auto_refresh_ports = enabled;
+            }
+            Message::DtrToggled(enabled) => {
+                if self.refuse_during_transfer() {
+                    return Task::none();
+                }
+                if let Some(port) = &self.serial_port {
+                    let result = port.control().write_data_terminal_ready(enabled);
+                    match result {
+                        Ok(()) => self.dtr = enabled,
+                        Err(e) => self.push_line(LineKind::Error, format!("❌ DTR: {}", e)),
+                    }
+                }
+            }
+            Message::RtsToggled(enabled) => {
+                if self.refuse_during_transfer() {
+                    return Task::none();
+                }
+                if let Some(port) = &self.serial_port {
+                    let result = port.control().write_request_to_send(enabled);
+                    match result {
+                        Ok(()) => self.rts = enabled,
+                        Err(e) => self.push_line(LineKind::Error, format!("❌ RTS: {}", e)),
+                    }
+                }
+            }
+            Message::ResetUsesRtsToggled(enabled) => {
+                self.reset_uses_rts = enabled;
+            }
+            Message::ResetPulseChanged(value) => {
+                self.reset_pulse_input = value;
+            }
+            Message::ResetTarget => {
+                if self.refuse_during_transfer() {
+                    return Task::none();
+                }
+                let Some(control) = self.serial_port.as_ref().map(PortIo::shared_control) else {
+                    return Task::none();
+                };
+                let pulse = match parse_reset_pulse(&self.reset_pulse_input) {
+                    Ok(pulse) => pulse,
+                    Err(e) => {
+                        self.push_line(LineKind::Error, format!("❌ {}", e));
+                        return Task::none();
+                    }
+                };
+                let with_rts = self.reset_uses_rts;
+                // The control lock is released during the pause so the lines stay usable
+                return Task::perform(
+                    async move {
+                        let set_lines = |level: bool| -> Result<(), String> {
+                            let mut port = control.lock().unwrap();
+                            port.write_data_terminal_ready(level)
+                                .map_err(|e| e.to_string())?;
+                            if with_rts {
+                                port.write_request_to_send(level)
+                                    .map_err(|e| e.to_string())?;
+                            }
+                            Ok(())
+                        };
+                        set_lines(true)?;
+                        tokio::time::sleep(pulse).await;
+                        set_lines(false)
+                    },
+                    Message::ResetPulseSent,
+                );
+            }
+            Message::ResetPulseSent(result) => match result {
+                Ok(()) => {
+                    self.dtr = false;
+                    if self.reset_uses_rts {
+                        self.rts = false;
+                    }
+                    self.push_line(LineKind::Info, "🔄 Импульс сброса отправлен".to_string());
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ Импульс сброса: {}", e)),
+            },
+            Message::AutoConnectToggled(enabled) => {
+                self.config.auto_connect = enabled;
+                self.save_config();
+            }
+            Message::RecentPortsToggled(enabled) => {
+                self.config.show_recent_ports = enabled;
+                self.save_config();
+            }
+            Message::ConnectRecent(recent) => {
+                if self.port_settings.connected {
+                    return Task::none();
+                }
+                self.port_settings.port_name = Some(recent.port_name);
+                self.port_settings.baud_rate = recent.baud_rate;
+                if let Some((data_bits, parity, stop_bits)) = parse_framing(&recent.framing) {
+                    self.port_settings.data_bits = data_bits;
+                    self.port_settings.parity = parity;
+                    self.port_settings.stop_bits = stop_bits;
+                }
+                return self.update(Message::ConnectPort);
+            }
+            Message::ProfileSelected(profile) => {
+                self.apply_profile(&profile);
+                self.push_line(
+                    LineKind::Info,
+                    format!("👤 Профиль {} загружен", profile.name),
+                );
+                self.profile_name_input = profile.name.clone();
+                self.selected_profile = Some(profile);
+            }
+            Message::ProfileNameChanged(value) => {
+                self.profile_name_input = value;
+            }
+            Message::SaveProfile => {
+                let name = self.profile_name_input.trim().to_string();
+                if name.is_empty() {
+                    self.push_line(LineKind::Error, "❌ Укажите имя профиля".to_string());
+                    return Task::none();
+                }
+                let profile = self.current_profile(name);
+                // Saving under an existing name overwrites that profile
+                match self
+                    .config
+                    .profiles
+                    .iter_mut()
+                    .find(|p| p.name == profile.name)
+                {
+                    Some(existing) => *existing = profile.clone(),
+                    None => self.config.profiles.push(profile.clone()),
+                }
+                self.save_config();
+                self.push_line(
+                    LineKind::Info,
+                    format!("👤 Профиль {} сохранен", profile.name),
+                );
+                self.selected_profile = Some(profile);
+            }
+            Message::DeleteProfile => {
+                if let Some(profile) = self.selected_profile.take() {
+                    self.config.profiles.retain(|p| p.name != profile.name);
+                    self.save_config();
+                    self.push_line(
+                        LineKind::Info,
+                        format!("👤 Профиль {} удален", profile.name),
+                    );
+                }
+            }
+            Message::PinnedUsbChanged(value) => {
+                self.pinned_usb_input = value;
+            }
+            Message::ApplyPinnedUsb => match parse_vid_pid(&self.pinned_usb_input) {
+                Ok(id) => {
+                    self.config.pinned_usb = id;
+                    self.save_config();
+                    match id {
+                        Some(id) => self.push_line(
+                            LineKind::Info,
+                            format!("📌 Закреплено устройство {}", vid_pid_label(id)),
+                        ),
+                        None => self.push_line(LineKind::Info, "📌 Закрепление снято".to_string()),
+                    }
+                    // A device already plugged in counts as freshly appeared
+                    self.pinned_present = false;
+                    self.check_pinned_device();
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::PinnedUsbToggled(enabled) => {
+                self.config.pinned_usb_enabled = enabled;
+                self.save_config();
+                self.pinned_present = false;
+                self.check_pinned_device();
+            }
+            Message::PortError(error) => {
+                self.push_line(LineKind::Error, format!("❌ {}", error));
+            }
+            Message::Port(generation, event) => {
+                if generation == self.port_generation && self.serial_port.is_some() {
+                    self.handle_port_event(event);
+                }
+            }
+            Message::PollIntervalChanged(value) => {
+                self.poll_interval_input = value;
+            }
+            Message::ApplyPollInterval => {
+                if self.refuse_during_transfer() {
+                    return Task::none();
+                }
+                match parse_poll_interval(&self.poll_interval_input) {
+                    Ok(ms) => {
+                        self.config.poll_interval_ms = ms;
+                        self.save_config();
+                        if let Some(port) = &self.serial_port {
+                            let _ = port.set_timeout(self.read_timeout());
+                            port.set_batch_window(self.tick_interval());
+                        }
+                        self.push_line(
+                            LineKind::Info,
+                            format!(
+                                "⏱️ Опрос порта каждые {} мс ({} раз/с): меньше — быстрее отклик, но выше нагрузка на CPU",
+                                ms,
+                                1000 / ms
+                            ),
+                        );
+                    }
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+                }
+            }
+            Message::ConnectRetriesChanged(value) => {
+                self.connect_retries_input = value;
+            }
+            Message::ConnectRetryDelayChanged(value) => {
+                self.connect_retry_delay_input = value;
+            }
+            Message::ApplyConnectRetry => match parse_connect_retry(
+                &self.connect_retries_input,
+                &self.connect_retry_delay_input,
+            ) {
+                Ok((retries, delay_ms)) => {
+                    self.config.connect_retries = retries;
+                    self.config.connect_retry_delay_ms = delay_ms;
+                    self.save_config();
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            other => unreachable!("not a connection message: {:?}", other),
+        }
+        Task::none()
+    }
+
+    // Preselects the remembered port and, if enabled and present, connects to it
+    pub(super) fn restore_last_port(&mut self) {
+        let Some(port_name) = self.config.last_port.clone() else {
+            return;
+        };
+        self.port_settings.port_name = Some(port_name.clone());
+        if let Some(baud_rate) = self.config.last_baud {
+            self.port_settings.baud_rate = baud_rate;
+        }
+        if !self.config.auto_connect {
+            return;
+        }
+        if !self.available_ports.contains(&port_name) {
+            self.push_line(
+                LineKind::Info,
+                format!("ℹ️ {} не найден, автоподключение пропущено", port_name),
+            );
+            return;
+        }
+        match self.open_port(&port_name) {
+            Ok(()) => self.push_line(
+                LineKind::Info,
+                format!(
+                    "🔁 Автоподключение к {} на {} baud",
+                    port_name, self.port_settings.baud_rate
+                ),
+            ),
+            Err(e) => {
+                self.push_open_error(format!("❌ Автоподключение к {} не удалось", port_name), &e)
+            }
+        }
+    }
+
+    // Listens at each common rate in turn on a worker thread; the port stays closed afterwards
+    // so the user can look at the scores before connecting
+    fn start_baud_scan(&mut self) -> Task<Message> {
+        let Some(port_name) = self.port_settings.port_name.clone() else {
+            self.push_line(LineKind::Info, "⚠️ Порт не выбран".to_string());
+            return Task::none();
+        };
+        if self.port_settings.connected || self.baud_scan.as_ref().is_some_and(|s| s.running) {
+            return Task::none();
+        }
+        self.baud_scan = Some(BaudScan {
+            results: Vec::new(),
+            running: true,
+        });
+        self.push_line(
+            LineKind::Info,
+            format!("🔎 Подбор скорости на {}...", port_name),
+        );
+
+        self.cancel_flag.store(false, Ordering::Relaxed);
+        let cancel = Arc::clone(&self.cancel_flag);
+        let settings = self.port_settings.clone();
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        std::thread::spawn(move || {
+            for rate in SCAN_BAUD_RATES {
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let builder = serialport::new(&port_name, rate)
+                    .data_bits(settings.data_bits)
+                    .stop_bits(settings.stop_bits)
+                    .timeout(Duration::from_millis(50));
+                let result = open_with_exclusivity(builder, settings.parity, settings.exclusive)
+                    .map_err(|e| e.to_string())
+                    .map(|mut port| {
+                        let bytes = listen_for(&mut *port, BAUD_SCAN_WINDOW);
+                        BaudScore {
+                            bytes: bytes.len(),
+                            text: text_score(&bytes),
+                        }
+                    });
+                let _ = sender.unbounded_send(Message::BaudScanStep(rate, result));
+            }
+            let _ = sender.unbounded_send(Message::BaudScanFinished);
+        });
+        Task::stream(receiver)
+    }
+
+    fn current_profile(&self, name: String) -> ConnectionProfile {
+        ConnectionProfile {
+            name,
+            port_name: self.port_settings.port_name.clone(),
+            baud_rate: self.port_settings.baud_rate,
+            framing: self.port_settings.framing(),
+            exclusive: self.port_settings.exclusive,
+            line_ending: self.line_ending,
+            commands: self.repeat_commands_input.clone(),
+        }
+    }
+
+    // Like a loaded session, a profile never touches the port of an open connection
+    fn apply_profile(&mut self, profile: &ConnectionProfile) {
+        if !self.port_settings.connected {
+            self.port_settings.port_name = profile.port_name.clone();
+        }
+        self.port_settings.baud_rate = profile.baud_rate;
+        self.port_settings.exclusive = profile.exclusive;
+        if let Some((data_bits, parity, stop_bits)) = parse_framing(&profile.framing) {
+            self.port_settings.data_bits = data_bits;
+            self.port_settings.parity = parity;
+            self.port_settings.stop_bits = stop_bits;
+        }
+        self.line_ending = profile.line_ending;
+        self.repeat_commands_input = profile.commands.clone();
+        self.note_framing_change();
+    }
+
+    // An open port keeps its framing and locking until reopened
+    pub(super) fn note_framing_change(&mut self) {
+        if self.port_settings.connected {
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "⚙️ Формат {}{} применится после переподключения",
+                    self.port_settings.framing(),
+                    if self.port_settings.exclusive {
+                        ", монопольно"
+                    } else {
+                        ", совместно"
+                    }
+                ),
+            );
+        }
+    }
+
+    // The reader thread checks its batch window and the stop flag between reads, so a read
+    // may never wait longer than one poll period
+    fn read_timeout(&self) -> Duration {
+        Duration::from_millis(self.poll_interval().as_millis().min(100) as u64)
+    }
+
+    pub(super) fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.config.poll_interval_ms.clamp(1, 1000))
+    }
+
+    // Opens the port with the current settings; shared by connect and reopen
+    fn open_port(&mut self, port_name: &str) -> Result<(), serialport::Error> {
+        let builder = serialport::new(port_name, self.port_settings.baud_rate)
+            .data_bits(self.port_settings.data_bits)
+            .stop_bits(self.port_settings.stop_bits)
+            .flow_control(self.port_settings.flow_control)
+            .timeout(self.read_timeout());
+        let (port, line_errors) = open_with_line_errors(
+            builder,
+            self.port_settings.parity,
+            self.port_settings.exclusive,
+        )?;
+        let (port, events) = PortIo::open(port, line_errors)?;
+        port.set_batch_window(self.tick_interval());
+        // Some drivers round or clamp unusual rates without failing the open
+        self.actual_baud = port.control().baud_rate().ok();
+        self.open_framing = Some(self.port_settings.framing());
+        if let Some(actual) = self.actual_baud {
+            if actual != self.port_settings.baud_rate {
+                self.push_line(
+                    LineKind::Error,
+                    format!(
+                        "⚠️ Драйвер изменил скорость: запрошено {}, установлено {} baud",
+                        self.port_settings.baud_rate, actual
+                    ),
+                );
+            }
+        }
+        self.port_settings.connected = true;
+        self.serial_port = Some(port);
+        self.port_generation += 1;
+        self.port_events = Some(events);
+        self.traffic.clear();
+        self.traffic_counts = (self.received_bytes, self.sent_bytes);
+        self.text_counts = (self.received_chars, self.received_lines);
+        self.traffic_svg = None;
+        self.value_samples.clear();
+        self.sparkline_svg = None;
+        self.framing_errors = self
+            .serial_port
+            .as_ref()
+            .is_some_and(PortIo::reports_line_errors)
+            .then_some(0);
+        self.recent_framing_errors.clear();
+        self.last_activity = Some(Instant::now());
+
+        // A reopen carries the running session over instead of starting a new one
+        if let Some(session) = self.session.as_mut() {
+            session.baud_rate = self.port_settings.baud_rate;
+        } else {
+            let started = chrono::Local::now();
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "🕒 {} начало сессии {} @{} {}",
+                    started.format("%Y-%m-%d %H:%M:%S"),
+                    port_name,
+                    self.port_settings.baud_rate,
+                    self.port_settings.framing()
+                ),
+            );
+            self.session = Some(ActiveSession {
+                port_name: port_name.to_string(),
+                baud_rate: self.port_settings.baud_rate,
+                started_at: Instant::now(),
+                started: started.format("%Y-%m-%d %H:%M:%S").to_string(),
+                rx_at_start: self.received_bytes,
+                tx_at_start: self.sent_bytes,
+            });
+        }
+
+        let baud_rate = self.port_settings.baud_rate;
+        let recent = RecentPort {
+            port_name: port_name.to_string(),
+            baud_rate,
+            framing: self.port_settings.framing(),
+        };
+        if self.config.last_port.as_deref() != Some(port_name)
+            || self.config.last_baud != Some(baud_rate)
+            || self.config.recent_ports.first() != Some(&recent)
+        {
+            self.config.last_port = Some(port_name.to_string());
+            self.config.last_baud = Some(baud_rate);
+            self.config
+                .recent_ports
+                .retain(|entry| entry.port_name != port_name);
+            self.config.recent_ports.insert(0, recent);
+            self.config.recent_ports.truncate(RECENT_PORTS_LEN);
+            self.save_config();
+        }
+        Ok(())
+    }
+
+    // One open attempt; transient failures schedule the next one after the configured delay.
+    // Permission errors are final since waiting does not change them
+    fn connect_attempt(&mut self, port_name: String, attempt: u32) -> Task<Message> {
+        let e = match self.open_port(&port_name) {
+            Ok(()) => {
+                self.push_line(
+                    LineKind::Info,
+                    format!(
+                        "✅ Подключен к {} на {} baud",
+                        port_name, self.port_settings.baud_rate
+                    ),
+                );
+                return Task::none();
+            }
+            Err(e) => e,
+        };
+        let attempts = self.config.connect_retries + 1;
+        let denied = e.kind() == serialport::ErrorKind::Io(io::ErrorKind::PermissionDenied);
+        if attempt < attempts && !denied {
+            let delay = Duration::from_millis(self.config.connect_retry_delay_ms);
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "⏳ Попытка {} из {} не удалась ({}), повтор через {} мс",
+                    attempt,
+                    attempts,
+                    e,
+                    delay.as_millis()
+                ),
+            );
+            return Task::perform(tokio::time::sleep(delay), move |_| {
+                Message::RetryConnect(port_name.clone(), attempt + 1)
+            });
+        }
+        self.push_open_error(format!("❌ Ошибка подключения к {}", port_name), &e);
+        Task::none()
+    }
+
+    // The raw OS error stays in the log; known causes get an extra line with what to do
+    fn push_open_error(&mut self, context: String, e: &serialport::Error) {
+        self.push_line(LineKind::Error, format!("{}: {}", context, e));
+        if let Some(hint) = open_error_hint(e) {
+            self.push_line(LineKind::Info, format!("💡 {}", hint));
+        }
+    }
+
+    // Drops the handle, which closes the port and stops the polling subscription.
+    // Also closes the current session and records its summary.
+    pub(super) fn close_port(&mut self) {
+        self.flush_frame_buffer();
+        self.mid_line = false;
+        self.stop_repeat();
+        self.finish_ping();
+        self.stop_script("прерван отключением");
+        self.stop_replay("прервано отключением");
+        self.stop_line_queue("прервана отключением");
+        self.activity.rx = false;
+        self.activity.tx = false;
+        self.driver_queue = None;
+        self.actual_baud = None;
+        self.open_framing = None;
+        self.text_rates = (0, 0);
+        self.dtr = false;
+        self.rts = false;
+        self.port_settings.connected = false;
+        // The transfer thread checks the flag between chunks, so the join is short; its
+        // TransferFinished then finds no transfer and is ignored
+        if let Some(transfer) = self.file_transfer.take() {
+            self.cancel_flag.store(true, Ordering::Relaxed);
+            if let Some(thread) = self.transfer_thread.take() {
+                let _ = thread.join();
+            }
+            self.sent_bytes += transfer.sent as u64;
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "⛔ {}: отправка {} прервана отключением",
+                    transfer.kind, transfer.file_name
+                ),
+            );
+        }
+        // Dropping the port joins its threads, so the device is free for a reopen.
+        // Whatever was still queued dies with the writer thread
+        self.serial_port = None;
+        self.port_events = None;
+        self.tx_queued = 0;
+        self.tx_queued_bytes = 0;
+        self.finish_session();
+    }
+
+    // Logs the summary of the running session and moves it into the history
+    fn finish_session(&mut self) {
+        if let Some(session) = self.session.take() {
+            let record = SessionRecord {
+                port_name: session.port_name,
+                baud_rate: session.baud_rate,
+                started: session.started,
+                duration: session.started_at.elapsed(),
+                rx_bytes: self.received_bytes.saturating_sub(session.rx_at_start),
+                tx_bytes: self.sent_bytes.saturating_sub(session.tx_at_start),
+            };
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "🕒 {} {}",
+                    chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+                    record.summary()
+                ),
+            );
+            self.session_history.push_back(record);
+            if self.session_history.len() > SESSION_HISTORY_LEN {
+                self.session_history.pop_front();
+            }
+        }
+    }
+
+    fn handle_port_event(&mut self, event: PortEvent) {
+        if let PortEvent::Written(job)
+        | PortEvent::WriteFailed { job, .. }
+        | PortEvent::Discarded(job) = &event
+        {
+            self.tx_queued = self.tx_queued.saturating_sub(1);
+            self.tx_queued_bytes = self.tx_queued_bytes.saturating_sub(job.data.len());
+        }
+        match event {
+            PortEvent::Received(data, at) => {
+                self.receive_chunk(&data);
+                // Timed by when the reply came off the port, not by the next tick
+                self.advance_ping(at);
+            }
+            PortEvent::ReadFailed { error, lost } => {
+                self.push_line(
+                    LineKind::Error,
+                    format!("❌ Ошибка чтения из порта: {}", error),
+                );
+                if lost {
+                    self.close_lost_port();
+                }
+            }
+            // Counted only once on the wire, so failed writes don't inflate TX
+            PortEvent::Written(job) => {
+                self.sent_bytes += job.data.len() as u64;
+                match job.kind {
+                    WriteKind::Quiet => {}
+                    WriteKind::Confirmed => {
+                        self.push_line(LineKind::Info, "✓ Данные отправлены".to_string())
+                    }
+                    WriteKind::Resend => self.push_line(
+                        LineKind::Info,
+                        format!(
+                            "🔁 Неотправленные данные ({} байт) отправлены повторно",
+                            job.data.len()
+                        ),
+                    ),
+                }
+                self.last_tx_frame = job.data;
+            }
+            PortEvent::WriteFailed { job, error, lost } => {
+                if job.kind == WriteKind::Resend {
+                    self.push_line(
+                        LineKind::Error,
+                        format!("❌ Повторная отправка не удалась: {}", error),
+                    );
+                    return;
+                }
+                self.push_line(
+                    LineKind::Error,
+                    format!("❌ Ошибка отправки данных: {}", error),
+                );
+                if lost {
+                    self.handle_lost_link(job.data);
+                }
+            }
+            PortEvent::LineErrors(count) => self.note_line_errors(count as u64),
+            PortEvent::Discarded(_) => {}
+        }
+    }
+
+    // A burst of these almost always means the baud rate or parity does not match the device.
+    // Only the first batch of a burst is logged, the rest are just counted
+    fn note_line_errors(&mut self, count: u64) {
+        *self.framing_errors.get_or_insert(0) += count;
+        let now = Instant::now();
+        while self
+            .recent_framing_errors
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > FRAMING_SPIKE_WINDOW)
+        {
+            self.recent_framing_errors.pop_front();
+        }
+        let recent: u64 = self.recent_framing_errors.iter().map(|(_, n)| n).sum();
+        if recent == 0 {
+            self.push_line(
+                LineKind::Error,
+                format!("⚠️ Ошибка кадра/чётности при приеме ({} байт)", count),
+            );
+        }
+        self.recent_framing_errors.push_back((now, count));
+        if recent < FRAMING_SPIKE_COUNT && recent + count >= FRAMING_SPIKE_COUNT {
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "💡 Много ошибок кадра: проверьте скорость и четность (сейчас {} baud, {})",
+                    self.port_settings.baud_rate,
+                    self.port_settings.framing()
+                ),
+            );
+        }
+    }
+
+    // The handle is dead after such an error, so the port is closed rather than left half-open
+    fn close_lost_port(&mut self) -> Option<String> {
+        let port_name = self.port_settings.port_name.clone()?;
+        self.close_port();
+        self.push_line(
+            LineKind::Error,
+            format!("🔌 Связь с {} потеряна, порт закрыт", port_name),
+        );
+        Some(port_name)
+    }
+
+    fn handle_lost_link(&mut self, data: Vec<u8>) {
+        let Some(port_name) = self.close_lost_port() else {
+            return;
+        };
+        if self.config.reconnect_on_send_error {
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "🔁 Переподключение к {} каждые {} мс",
+                    port_name, self.config.connect_retry_delay_ms
+                ),
+            );
+            self.send_recovery = Some(SendRecovery {
+                port_name,
+                data,
+                attempt: 0,
+            });
+        }
+    }
+
+    // One reopen attempt per RecoverSend tick. Permission errors end the recovery at once
+    // since waiting does not change them
+    fn recover_send(&mut self) {
+        // The user may have reconnected by hand in the meantime
+        if self.port_settings.connected {
+            self.send_recovery = None;
+            return;
+        }
+        let Some(recovery) = self.send_recovery.as_mut() else {
+            return;
+        };
+        recovery.attempt += 1;
+        let (port_name, attempt) = (recovery.port_name.clone(), recovery.attempt);
+        let attempts = self.config.connect_retries + 1;
+        match self.open_port(&port_name) {
+            Ok(()) => {
+                let recovery = self.send_recovery.take().expect("checked above");
+                self.push_line(
+                    LineKind::Info,
+                    format!(
+                        "✅ Связь с {} восстановлена (попытка {})",
+                        port_name, attempt
+                    ),
+                );
+                if self.config.resend_after_reconnect {
+                    self.resend_once(recovery.data);
+                }
+            }
+            Err(e) => {
+                let denied = e.kind() == serialport::ErrorKind::Io(io::ErrorKind::PermissionDenied);
+                if attempt < attempts && !denied {
+                    self.push_line(
+                        LineKind::Info,
+                        format!(
+                            "⏳ Переподключение {} из {} не удалось ({})",
+                            attempt, attempts, e
+                        ),
+                    );
+                    return;
+                }
+                self.send_recovery = None;
+                self.push_open_error(
+                    format!("❌ Не удалось переподключиться к {}", port_name),
+                    &e,
+                );
+            }
+        }
+    }
+
+    // Bypasses write_bytes: the data already went through the hook and the recording,
+    // and a second failure must not start another recovery
+    fn resend_once(&mut self, data: Vec<u8>) {
+        self.queue_write(data, WriteKind::Resend);
+    }
+
+    fn apply_port_infos(&mut self, ports: Vec<SerialPortInfo>) {
+        self.apply_port_list(ports.iter().map(|p| p.port_name.clone()).collect());
+        self.port_infos = ports;
+        self.check_pinned_device();
+    }
+
+    // Connects when the pinned USB device appears and disconnects when it goes away.
+    // Acts only on changes, so a failed open is not retried until the device is replugged.
+    pub(super) fn check_pinned_device(&mut self) {
+        let Some((vid, pid)) = self
+            .config
+            .pinned_usb
+            .filter(|_| self.config.pinned_usb_enabled)
+        else {
+            return;
+        };
+        let found = self
+            .port_infos
+            .iter()
+            .find_map(|info| match &info.port_type {
+                SerialPortType::UsbPort(usb) if usb.vid == vid && usb.pid == pid => {
+                    Some(info.port_name.clone())
+                }
+                _ => None,
+            });
+        let appeared = found.is_some() && !self.pinned_present;
+        self.pinned_present = found.is_some();
+
+        match found {
+            Some(port_name) if appeared && !self.port_settings.connected => {
+                if let Some(baud_rate) = self.config.last_baud {
+                    self.port_settings.baud_rate = baud_rate;
+                }
+                self.port_settings.port_name = Some(port_name.clone());
+                match self.open_port(&port_name) {
+                    Ok(()) => {
+                        self.pinned_port = Some(port_name.clone());
+                        self.push_line(
+                            LineKind::Info,
+                            format!(
+                                "📌 Подключен к {} ({}) на {} baud",
+                                port_name,
+                                vid_pid_label((vid, pid)),
+                                self.port_settings.baud_rate
+                            ),
+                        );
+                    }
+                    Err(e) => {
+                        self.push_open_error(format!("❌ Ошибка подключения к {}", port_name), &e)
+                    }
+                }
+            }
+            None => {
+                let Some(port_name) = self.pinned_port.take() else {
+                    return;
+                };
+                if self.port_settings.connected
+                    && self.port_settings.port_name.as_deref() == Some(port_name.as_str())
+                {
+                    self.close_port();
+                    self.push_line(
+                        LineKind::Info,
+                        format!("📌 Устройство {} отключено, порт закрыт", port_name),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Replaces the port list, keeping the selection if that port is still present.
+    // Only logs which ports appeared or vanished, so periodic polling stays quiet.
+    fn apply_port_list(&mut self, ports: Vec<String>) {
+        let added: Vec<String> = ports
+            .iter()
+            .filter(|p| !self.available_ports.contains(p))
+            .cloned()
+            .collect();
+        let removed: Vec<String> = self
+            .available_ports
+            .iter()
+            .filter(|p| !ports.contains(p))
+            .cloned()
+            .collect();
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        if !added.is_empty() {
+            self.push_line(
+                LineKind::Info,
+                format!("➕ Появились порты: {}", added.join(", ")),
+            );
+        }
+        if !removed.is_empty() {
+            self.push_line(
+                LineKind::Info,
+                format!("➖ Пропали порты: {}", removed.join(", ")),
+            );
+        }
+        self.available_ports = ports;
+
+        let selection_gone = self
+            .port_settings
+            .port_name
+            .as_ref()
+            .is_none_or(|name| !self.available_ports.contains(name));
+        if selection_gone && !self.port_settings.connected {
+            self.port_settings.port_name = self.available_ports.first().cloned();
+        }
+
+        if self.available_ports.is_empty() {
+            self.push_line(LineKind::Info, "⚠️ COM порты не найдены".to_string());
+        }
+    }
+}
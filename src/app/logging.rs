@@ -0,0 +1,447 @@
+use super::*;
+
+// Collects exactly `target` received bytes across reads for saving or inspection
+#[derive(Debug, Clone)]
+pub struct ByteCapture {
+    pub target: usize,
+    pub data: Vec<u8>,
+}
+
+impl ByteCapture {
+    pub fn is_complete(&self) -> bool {
+        self.data.len() >= self.target
+    }
+}
+
+pub(super) const MAX_CAPTURE_BYTES: usize = 16 << 20;
+
+// A recording being sent back out, one chunk per ReplayStep
+pub struct Replay {
+    pub name: String,
+    pub chunks: Vec<Vec<u8>>,
+    pub recording: TxRecording,
+    pub index: usize,
+    pub speed: f64,
+}
+
+impl ComTerminal {
+    pub(super) fn update_logging(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SaveLog => {
+                return Task::perform(
+                    async {
+                        rfd::FileDialog::new()
+                            .set_file_name("com_log.txt")
+                            .save_file()
+                    },
+                    Message::LogPathChosen,
+                );
+            }
+            Message::LogPathChosen(Some(path)) => {
+                if let Err(e) = std::fs::File::create(&path) {
+                    self.push_line(
+                        LineKind::Error,
+                        format!("❌ Не удалось создать {}: {}", path.display(), e),
+                    );
+                    return Task::none();
+                }
+                self.log_file_path = Some(path.display().to_string());
+                self.log_written = 0;
+                self.push_line(
+                    LineKind::Info,
+                    format!("💾 Лог сохраняется в {}", path.display()),
+                );
+                self.flush_log();
+            }
+            Message::LogPathChosen(None) => {}
+            Message::ExportDirectionSelected(direction) => {
+                self.export_direction = direction;
+            }
+            Message::ExportFormatSelected(format) => {
+                self.export_format = format;
+            }
+            Message::ExportLineEndingSelected(ending) => {
+                self.export_line_ending = ending;
+            }
+            Message::AutosaveKbChanged(value) => {
+                self.autosave_kb_input = value;
+            }
+            Message::AutosaveSecsChanged(value) => {
+                self.autosave_secs_input = value;
+            }
+            Message::ApplyAutosave => {
+                match (
+                    parse_autosave(&self.autosave_kb_input),
+                    parse_autosave(&self.autosave_secs_input),
+                ) {
+                    (Ok(kb), Ok(secs)) => {
+                        self.config.autosave_kb = kb;
+                        self.config.autosave_secs = secs;
+                        self.save_config();
+                        self.push_line(
+                            LineKind::Info,
+                            format!(
+                                "💾 Автосохранение: каждые {} КБ / {} с (0 = выкл)",
+                                kb, secs
+                            ),
+                        );
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        self.push_line(LineKind::Error, format!("❌ {}", e))
+                    }
+                }
+            }
+            Message::SaveSession => {
+                let json = match serde_json::to_string_pretty(&self.snapshot()) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        self.push_line(LineKind::Error, format!("❌ Сессия: {}", e));
+                        return Task::none();
+                    }
+                };
+                return Task::perform(async move { save_session(json) }, Message::SessionSaved);
+            }
+            Message::CaptureSizeChanged(value) => {
+                self.capture_size_input = value;
+            }
+            Message::StartCapture => match parse_capture_size(&self.capture_size_input) {
+                Ok(target) => {
+                    self.capture = Some(ByteCapture {
+                        target,
+                        data: Vec::with_capacity(target),
+                    });
+                    self.push_line(
+                        LineKind::Info,
+                        format!("📥 Захват следующих {} байт", target),
+                    );
+                }
+                Err(e) => self.push_line(LineKind::Error, format!("❌ {}", e)),
+            },
+            Message::CancelCapture => {
+                self.capture = None;
+            }
+            Message::SaveCapture => {
+                if let Some(capture) = &self.capture {
+                    let data = capture.data.clone();
+                    return Task::perform(async move { save_capture(data) }, Message::CaptureSaved);
+                }
+            }
+            Message::StartTxRecording => {
+                self.tx_recording = Some((Instant::now(), TxRecording::default()));
+                self.push_line(LineKind::Info, "⏺ Запись отправляемых данных".to_string());
+            }
+            Message::StopTxRecording => {
+                let Some((_, recording)) = self.tx_recording.take() else {
+                    return Task::none();
+                };
+                self.push_line(
+                    LineKind::Info,
+                    format!(
+                        "⏹ Запись остановлена: {} фрагментов за {}",
+                        recording.chunks.len(),
+                        format_uptime(recording.duration())
+                    ),
+                );
+                let json = match serde_json::to_string_pretty(&recording) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        self.push_line(LineKind::Error, format!("❌ Запись: {}", e));
+                        return Task::none();
+                    }
+                };
+                self.loaded_recording = Some(("запись".to_string(), recording));
+                return Task::perform(async move { save_recording(json) }, Message::RecordingSaved);
+            }
+            Message::RecordingSaved(Ok(Some(path))) => {
+                self.push_line(LineKind::Info, format!("💾 Запись сохранена: {}", path));
+            }
+            Message::RecordingSaved(Ok(None)) => {}
+            Message::RecordingSaved(Err(e)) => {
+                self.push_line(
+                    LineKind::Error,
+                    format!("❌ Не удалось сохранить запись: {}", e),
+                );
+            }
+            Message::LoadRecording => {
+                return Task::perform(async { pick_file_bytes() }, Message::RecordingLoaded);
+            }
+            Message::RecordingLoaded(Ok(Some((name, data)))) => {
+                match serde_json::from_slice::<TxRecording>(&data) {
+                    Ok(recording) => {
+                        self.push_line(
+                            LineKind::Info,
+                            format!(
+                                "📂 Запись {}: {} фрагментов за {}",
+                                name,
+                                recording.chunks.len(),
+                                format_uptime(recording.duration())
+                            ),
+                        );
+                        self.loaded_recording = Some((name, recording));
+                    }
+                    Err(e) => self.push_line(
+                        LineKind::Error,
+                        format!("❌ {}: не файл записи ({})", name, e),
+                    ),
+                }
+            }
+            Message::RecordingLoaded(Ok(None)) => {}
+            Message::RecordingLoaded(Err(e)) => {
+                self.push_line(LineKind::Error, format!("❌ Ошибка чтения файла: {}", e));
+            }
+            Message::ReplaySpeedChanged(value) => {
+                self.replay_speed_input = value;
+            }
+            Message::StartReplay => {
+                let Some((name, recording)) = self.loaded_recording.clone() else {
+                    return Task::none();
+                };
+                if self.port_settings.read_only {
+                    self.push_line(
+                        LineKind::Error,
+                        "❌ Порт открыт только для чтения".to_string(),
+                    );
+                    return Task::none();
+                }
+                if !self.can_transmit() || self.replay.is_some() || self.refuse_during_transfer() {
+                    return Task::none();
+                }
+                let speed = match parse_replay_speed(&self.replay_speed_input) {
+                    Ok(speed) => speed,
+                    Err(e) => {
+                        self.push_line(LineKind::Error, format!("❌ {}", e));
+                        return Task::none();
+                    }
+                };
+                let chunks = match recording.decode() {
+                    Ok(chunks) if !chunks.is_empty() => chunks,
+                    Ok(_) => {
+                        self.push_line(LineKind::Info, format!("⚠️ Запись {} пуста", name));
+                        return Task::none();
+                    }
+                    Err(e) => {
+                        self.push_line(LineKind::Error, format!("❌ {}: {}", name, e));
+                        return Task::none();
+                    }
+                };
+                self.push_line(
+                    LineKind::Info,
+                    format!("⏯ Воспроизведение {} (x{})", name, speed),
+                );
+                self.replay_generation += 1;
+                self.replay = Some(Replay {
+                    name,
+                    chunks,
+                    recording,
+                    index: 0,
+                    speed,
+                });
+                return self.schedule_replay_step();
+            }
+            Message::ReplayStep(generation) => {
+                if generation != self.replay_generation {
+                    return Task::none();
+                }
+                // The transfer thread writes through its own handle, so chunks would land inside the file
+                if self.file_transfer.is_some() {
+                    self.stop_replay("прервано: идет передача файла");
+                    return Task::none();
+                }
+                let Some(replay) = self.replay.as_mut() else {
+                    return Task::none();
+                };
+                let data = replay.chunks[replay.index].clone();
+                replay.index += 1;
+                if !self.can_transmit() {
+                    self.stop_replay("прервано: порт недоступен");
+                    return Task::none();
+                }
+                self.send_with_echo(hex::bytes_to_hex(&data), &data);
+                return self.schedule_replay_step();
+            }
+            Message::StopReplay => {
+                self.stop_replay("остановлено");
+            }
+            Message::CaptureSaved(Ok(Some(path))) => {
+                self.push_line(LineKind::Info, format!("💾 Захват сохранен: {}", path));
+            }
+            Message::CaptureSaved(Ok(None)) => {}
+            Message::CaptureSaved(Err(e)) => {
+                self.push_line(
+                    LineKind::Error,
+                    format!("❌ Не удалось сохранить захват: {}", e),
+                );
+            }
+            Message::SessionSaved(Ok(Some(path))) => {
+                self.push_line(LineKind::Info, format!("💾 Сессия сохранена: {}", path));
+            }
+            Message::SessionSaved(Ok(None)) => {}
+            Message::SessionSaved(Err(e)) => {
+                self.push_line(
+                    LineKind::Error,
+                    format!("❌ Не удалось сохранить сессию: {}", e),
+                );
+            }
+            Message::LoadSession => {
+                return Task::perform(async { pick_file_bytes() }, Message::SessionLoaded);
+            }
+            Message::SessionLoaded(Ok(Some((name, data)))) => {
+                match serde_json::from_slice::<SessionSnapshot>(&data) {
+                    Ok(snapshot) => {
+                        self.apply_snapshot_settings(&snapshot);
+                        self.push_line(
+                            LineKind::Info,
+                            format!(
+                                "📂 Сессия {}: {} строк, сохранена {}",
+                                name,
+                                snapshot.lines.len(),
+                                snapshot.saved_at.format("%Y-%m-%d %H:%M:%S")
+                            ),
+                        );
+                        self.loaded_session = Some((name, snapshot));
+                    }
+                    Err(e) => self.push_line(
+                        LineKind::Error,
+                        format!("❌ {}: не файл сессии ({})", name, e),
+                    ),
+                }
+            }
+            Message::SessionLoaded(Ok(None)) => {}
+            Message::SessionLoaded(Err(e)) => {
+                self.push_line(LineKind::Error, format!("❌ Ошибка чтения файла: {}", e));
+            }
+            Message::CloseLoadedSession => {
+                self.loaded_session = None;
+            }
+            other => unreachable!("not a logging message: {:?}", other),
+        }
+        Task::none()
+    }
+
+    fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            saved_at: chrono::Local::now(),
+            port_name: self.port_settings.port_name.clone(),
+            baud_rate: self.port_settings.baud_rate,
+            framing: self.port_settings.framing(),
+            exclusive: self.port_settings.exclusive,
+            connected: self.port_settings.connected,
+            session_started: self.session.as_ref().map(|s| s.started.clone()),
+            session_duration_secs: self
+                .session
+                .as_ref()
+                .map(|s| s.started_at.elapsed().as_secs_f64()),
+            sent_bytes: self.sent_bytes,
+            received_bytes: self.received_bytes,
+            lines: self
+                .terminal_output
+                .iter()
+                .map(SnapshotLine::from_line)
+                .collect(),
+        }
+    }
+
+    // Port parameters from a loaded session; an open connection keeps running as is
+    fn apply_snapshot_settings(&mut self, snapshot: &SessionSnapshot) {
+        if !self.port_settings.connected {
+            self.port_settings.port_name = snapshot.port_name.clone();
+        }
+        self.port_settings.baud_rate = snapshot.baud_rate;
+        self.port_settings.exclusive = snapshot.exclusive;
+        match parse_framing(&snapshot.framing) {
+            Some((data_bits, parity, stop_bits)) => {
+                self.port_settings.data_bits = data_bits;
+                self.port_settings.parity = parity;
+                self.port_settings.stop_bits = stop_bits;
+            }
+            None => self.push_line(
+                LineKind::Error,
+                format!("❌ Неизвестный формат кадра: {}", snapshot.framing),
+            ),
+        }
+        self.note_framing_change();
+    }
+
+    pub(super) fn stop_replay(&mut self, reason: &str) {
+        if let Some(replay) = self.replay.take() {
+            self.push_line(
+                LineKind::Info,
+                format!(
+                    "⏯ Воспроизведение {} {} ({} из {})",
+                    replay.name,
+                    reason,
+                    replay.index,
+                    replay.chunks.len()
+                ),
+            );
+        }
+    }
+
+    // Sleeps until the next chunk is due, or reports the end of the recording
+    fn schedule_replay_step(&mut self) -> Task<Message> {
+        let Some(replay) = &self.replay else {
+            return Task::none();
+        };
+        if replay.index >= replay.chunks.len() {
+            self.stop_replay("завершено");
+            return Task::none();
+        }
+        let delay = replay.recording.delay_before(replay.index, replay.speed);
+        let generation = self.replay_generation;
+        Task::perform(tokio::time::sleep(delay), move |_| {
+            Message::ReplayStep(generation)
+        })
+    }
+
+    // Takes raw bytes for a running capture; the terminal still shows everything
+    pub(super) fn feed_capture(&mut self, bytes: &[u8]) {
+        let Some(capture) = self.capture.as_mut().filter(|c| !c.is_complete()) else {
+            return;
+        };
+        let take = (capture.target - capture.data.len()).min(bytes.len());
+        capture.data.extend_from_slice(&bytes[..take]);
+        if capture.is_complete() {
+            let target = capture.target;
+            self.push_line(LineKind::Info, format!("📥 Захвачено {} байт", target));
+        }
+    }
+
+    // Appends lines not yet written to the log file. Both autosave triggers land here
+    // and it resets both, so whichever fires first wins and the other starts over.
+    pub(super) fn flush_log(&mut self) {
+        self.rx_since_flush = 0;
+        self.last_flush = Some(Instant::now());
+        let Some(path) = self.log_file_path.clone() else {
+            return;
+        };
+        let content: String = self
+            .terminal_output
+            .iter()
+            .skip(self.log_written)
+            .filter_map(|line| {
+                line.export(
+                    self.export_direction,
+                    self.export_format,
+                    self.config.tag_ports,
+                )
+            })
+            .map(|line| line + "\n")
+            .collect();
+        let content = self.export_line_ending.apply(content);
+        let result = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(content.as_bytes()));
+        match result {
+            Ok(()) => {
+                self.log_written = self.terminal_output.len();
+                self.dirty = false;
+            }
+            Err(e) => self.push_line(
+                LineKind::Error,
+                format!("❌ Ошибка записи лога {}: {}", path, e),
+            ),
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferKind {
+    Raw,
+    Zmodem,
+}
+
+impl std::fmt::Display for TransferKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferKind::Raw => write!(f, "RAW"),
+            TransferKind::Zmodem => write!(f, "ZMODEM"),
+        }
+    }
+}
+
+// A file checked before flashing: CRC-32 is computed in the background
+#[derive(Debug, Clone)]
+pub struct VerifiedFile {
+    pub name: String,
+    pub data: Arc<Vec<u8>>,
+    pub hashed: usize,
+    pub crc: Option<u32>,
+}
+
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct FileTransfer {
+    pub kind: TransferKind,
+    pub file_name: String,
+    pub sent: usize,
+    pub total: usize,
+    // The device sent XOFF and has not released it yet
+    pub paused: bool,
+}
+
+impl ComTerminal {
+    pub(super) fn update_transfer(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SendFile(kind) => {
+                return Task::perform(async { pick_file_bytes() }, move |result| {
+                    Message::FileChosen(kind, result)
+                });
+            }
+            Message::FileChosen(TransferKind::Raw, Ok(Some((name, data)))) if self.file_as_hex => {
+                match parse_hex_file(&String::from_utf8_lossy(&data)) {
+                    Ok(bytes) => return self.request_transfer(TransferKind::Raw, name, bytes),
+                    Err(e) => self.push_line(LineKind::Error, format!("❌ {}: {}", name, e)),
+                }
+            }
+            Message::FileChosen(kind, Ok(Some((name, data)))) => {
+                return self.request_transfer(kind, name, data);
+            }
+            Message::FileChosen(_, Ok(None)) => {}
+            Message::FileChosen(_, Err(e)) => {
+                self.push_line(LineKind::Error, format!("❌ Ошибка чтения файла: {}", e));
+            }
+            Message::VerifyFile => {
+                return Task::perform(async { pick_file_bytes() }, Message::VerifyFileChosen);
+            }
+            Message::VerifyFileChosen(Ok(Some((name, data)))) => {
+                return self.start_hashing(name, data);
+            }
+            Message::VerifyFileChosen(Ok(None)) => {}
+            Message::VerifyFileChosen(Err(e)) => {
+                self.push_line(LineKind::Error, format!("❌ Ошибка чтения файла: {}", e));
+            }
+            Message::HashProgress(generation, hashed) => {
+                if generation != self.hash_generation {
+                    return Task::none();
+                }
+                if let Some(file) = &mut self.verified_file {
+                    file.hashed = hashed;
+                }
+            }
+            Message::HashFinished(generation, crc) => {
+                if generation != self.hash_generation {
+                    return Task::none();
+                }
+                if let Some(file) = &mut self.verified_file {
+                    file.hashed = file.data.len();
+                    file.crc = Some(crc);
+                    let line = format!(
+                        "🧮 {}: {} байт, CRC-32 {:08X}",
+                        file.name,
+                        file.data.len(),
+                        crc
+                    );
+                    self.push_line(LineKind::Info, line);
+                }
+            }
+            Message::ExpectedCrcChanged(value) => {
+                self.expected_crc_input = value;
+            }
+            Message::SendVerified => {
+                if !self.verified_crc_matches() {
+                    self.push_line(
+                        LineKind::Error,
+                        "❌ CRC-32 файла не совпадает с ожидаемым, файл не отправлен".to_string(),
+                    );
+                    return Task::none();
+                }
+                if let Some(file) = self.verified_file.clone() {
+                    return self.request_transfer(
+                        TransferKind::Raw,
+                        file.name,
+                        file.data.as_ref().clone(),
+                    );
+                }
+            }
+            Message::ConfirmLargeTransfer => {
+                if let Some((kind, name, data)) = self.pending_transfer.take() {
+                    return self.start_transfer(kind, name, data);
+                }
+            }
+            Message::CancelLargeTransfer => {
+                if let Some((_, name, _)) = self.pending_transfer.take() {
+                    self.push_line(LineKind::Info, format!("📤 Отправка {} отменена", name));
+                }
+            }
+            Message::FileAsHexToggled(enabled) => {
+                self.file_as_hex = enabled;
+            }
+            Message::XonXoffToggled(enabled) => {
+                self.xon_xoff = enabled;
+            }
+            Message::TransferProgress(sent) => {
+                if let Some(transfer) = &mut self.file_transfer {
+                    transfer.sent = sent;
+                }
+            }
+            Message::TransferPaused(paused) => {
+                if let Some(transfer) = &mut self.file_transfer {
+                    transfer.paused = paused;
+                }
+            }
+            Message::TransferReceived(bytes) => {
+                self.receive_chunk(&bytes);
+            }
+            Message::TransferFinished(result) => {
+                self.last_activity = Some(Instant::now());
+                self.transfer_thread = None;
+                if let Some(transfer) = self.file_transfer.take() {
+                    self.sent_bytes += transfer.sent as u64;
+                    match result {
+                        Ok(()) => self.push_line(
+                            LineKind::Info,
+                            format!(
+                                "✅ {}: {} отправлен ({} байт)",
+                                transfer.kind, transfer.file_name, transfer.total
+                            ),
+                        ),
+                        Err(e) => self.push_line(
+                            LineKind::Error,
+                            format!(
+                                "❌ {}: ошибка отправки {}: {}",
+                                transfer.kind, transfer.file_name, e
+                            ),
+                        ),
+                    }
+                }
+            }
+            other => unreachable!("not a transfer message: {:?}", other),
+        }
+        Task::none()
+    }
+
+    // Large files wait for confirmation; everything else starts right away
+    fn request_transfer(
+        &mut self,
+        kind: TransferKind,
+        file_name: String,
+        data: Vec<u8>,
+    ) -> Task<Message> {
+        let limit = self.config.large_file_kb;
+        if limit > 0 && data.len() as u64 > limit * 1024 {
+            self.pending_transfer = Some((kind, file_name, data));
+            return Task::none();
+        }
+        self.start_transfer(kind, file_name, data)
+    }
+
+    // Time to push `len` bytes at the effective baud rate with the current framing
+    pub(super) fn transfer_estimate(&self, len: usize) -> Duration {
+        Duration::from_secs_f64(estimate_transfer_secs(
+            len,
+            self.actual_baud.unwrap_or(self.port_settings.baud_rate),
+            self.port_settings.bits_per_byte(),
+        ))
+    }
+
+    // Runs the sender on a worker thread that holds the port for the whole transfer;
+    // polling and manual sends are paused until it reports back
+    fn start_transfer(
+        &mut self,
+        kind: TransferKind,
+        file_name: String,
+        data: Vec<u8>,
+    ) -> Task<Message> {
+        if self.serial_port.is_none() {
+            self.push_line(LineKind::Info, "⚠️ Порт не подключен".to_string());
+            return Task::none();
+        }
+        if self.file_transfer.is_some() {
+            self.push_line(
+                LineKind::Info,
+                "⚠️ Передача файла уже выполняется".to_string(),
+            );
+            return Task::none();
+        }
+        if self.port_settings.read_only {
+            self.push_line(
+                LineKind::Error,
+                "❌ Порт открыт только для чтения".to_string(),
+            );
+            return Task::none();
+        }
+        let Some(exclusive) = self.serial_port.as_ref().map(PortIo::exclusive) else {
+            return Task::none();
+        };
+        self.stop_replay("прервано: начата передача файла");
+        self.stop_line_queue("прервана: начата передача файла");
+        self.finish_ping();
+        self.stop_script("прерван: начата передача файла");
+
+        self.push_line(
+            LineKind::Info,
+            format!("📤 {}: отправка {} ({} байт)", kind, file_name, data.len()),
+        );
+        self.file_transfer = Some(FileTransfer {
+            kind,
+            file_name: file_name.clone(),
+            sent: 0,
+            total: data.len(),
+            paused: false,
+        });
+
+        self.cancel_flag.store(false, Ordering::Relaxed);
+        let cancel = Arc::clone(&self.cancel_flag);
+        let xon_xoff = self.xon_xoff && self.port_settings.flow_control == FlowControl::Software;
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        self.transfer_thread = Some(std::thread::spawn(move || {
+            // Reading is paused during a transfer, so the reader handle does both directions
+            let mut port = exclusive.lock();
+            let progress = |sent| {
+                let _ = sender.unbounded_send(Message::TransferProgress(sent));
+            };
+            let mut flow = |event| {
+                let _ = sender.unbounded_send(match event {
+                    FlowEvent::Paused(paused) => Message::TransferPaused(paused),
+                    FlowEvent::Received(bytes) => Message::TransferReceived(bytes),
+                });
+            };
+            let result = match kind {
+                TransferKind::Raw => send_raw(
+                    &mut **port,
+                    &data,
+                    &cancel,
+                    progress,
+                    xon_xoff.then_some(&mut flow as &mut dyn FnMut(FlowEvent)),
+                ),
+                TransferKind::Zmodem => {
+                    zmodem::send_file(&mut **port, &file_name, &data, &cancel, progress)
+                }
+            };
+            if cancel.load(Ordering::Relaxed) {
+                let _ = port.clear(ClearBuffer::Output);
+            }
+            let _ = sender.unbounded_send(Message::TransferFinished(result));
+        }));
+        Task::stream(receiver)
+    }
+
+    // Only a finished hash that equals the one typed in lets the file go out
+    pub(super) fn verified_crc_matches(&self) -> bool {
+        let expected = parse_crc(&self.expected_crc_input);
+        self.verified_file
+            .as_ref()
+            .and_then(|file| file.crc)
+            .is_some_and(|crc| expected == Some(crc))
+    }
+
+    fn start_hashing(&mut self, name: String, data: Vec<u8>) -> Task<Message> {
+        let data = Arc::new(data);
+        self.verified_file = Some(VerifiedFile {
+            name,
+            data: Arc::clone(&data),
+            hashed: 0,
+            crc: None,
+        });
+        self.hash_generation += 1;
+        let generation = self.hash_generation;
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        std::thread::spawn(move || {
+            let mut crc = 0xFFFF_FFFF;
+            for (i, chunk) in data.chunks(HASH_CHUNK_SIZE).enumerate() {
+                crc = zmodem::crc32_update(crc, chunk);
+                let hashed = i * HASH_CHUNK_SIZE + chunk.len();
+                let _ = sender.unbounded_send(Message::HashProgress(generation, hashed));
+            }
+            let _ = sender.unbounded_send(Message::HashFinished(generation, !crc));
+        });
+        Task::stream(receiver)
+    }
+
+    // The transfer thread holds the port for the whole file: waiting on it would freeze the UI
+    // and writing past it would corrupt the file
+    pub(super) fn refuse_during_transfer(&mut self) -> bool {
+        if self.file_transfer.is_none() {
+            return false;
+        }
+        self.push_line(
+            LineKind::Info,
+            "⚠️ Порт занят передачей файла, дождитесь ее окончания".to_string(),
+        );
+        true
+    }
+}
@@ -0,0 +1,2085 @@
+use super::*;
+use crate::ansi;
+use iced::widget::{
+    button, checkbox, column, container, horizontal_space, pick_list, progress_bar, radio,
+    rich_text, row, scrollable, span, svg, text, text_editor, text_input, tooltip,
+};
+
+impl ComTerminal {
+    pub(super) fn view(&self, window: window::Id) -> Element<'_, Message> {
+        if self.log_window == Some(window) {
+            return self.log_window_view();
+        }
+        let nav_bar = row![
+            self.nav_button("🖥️ Терминал", WindowState::Terminal),
+            self.nav_button("⚙️ Настройки", WindowState::Settings),
+            self.nav_button("📊 Мониторинг", WindowState::Monitor),
+            self.nav_button("📁 Файлы", WindowState::FileView),
+            self.nav_button("🔀 Сравнение", WindowState::Compare),
+        ]
+        .spacing(5)
+        .padding([10, 20]);
+
+        let content = match self.current_window {
+            WindowState::Terminal => self.terminal_view(),
+            WindowState::Settings => self.settings_view(),
+            WindowState::Monitor => self.monitor_view(),
+            WindowState::FileView => self.file_view(),
+            WindowState::Compare => self.compare_view(),
+        };
+
+        container(column![nav_bar, content].spacing(10))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    // "115200", or "requested → actual" when the driver adjusted the rate
+    fn baud_label(&self) -> String {
+        match self.actual_baud {
+            Some(actual) if actual != self.port_settings.baud_rate => format!(
+                "запрошено {} → фактически {}",
+                self.port_settings.baud_rate, actual
+            ),
+            _ => self.port_settings.baud_rate.to_string(),
+        }
+    }
+
+    // "115200, 7E1" with the framing the port was really opened with
+    fn connection_label(&self) -> String {
+        let framing = self
+            .open_framing
+            .clone()
+            .unwrap_or_else(|| self.port_settings.framing());
+        format!("{}, {}", self.baud_label(), framing)
+    }
+
+    fn render_options(&self) -> RenderOptions<'_> {
+        RenderOptions {
+            show_time: self.show_timestamps,
+            show_delta: self.show_deltas,
+            show_eol: self.show_eol,
+            max_len: self.config.max_line_len,
+            wrap_at: self.config.rx_wrap,
+            prompt: &self.config.prompt,
+            strip_prompt: self.config.prompt_mode == PromptMode::Strip,
+            display_mode: self.display_mode,
+            hexdump_width: hexdump_width(self.config.hexdump_width),
+        }
+    }
+
+    // Falls back to the system monospace face if the embedded one failed to load
+    fn terminal_font(&self) -> Font {
+        match (self.config.monospace_terminal, self.terminal_font_loaded) {
+            (false, _) => Font::DEFAULT,
+            (true, true) => TERMINAL_FONT,
+            (true, false) => Font::MONOSPACE,
+        }
+    }
+
+    // Echoed prompt lines fade out in dim mode
+    fn line_color(&self, line: &TerminalLine) -> Color {
+        let color = self.config.line_colors.color(line.kind);
+        if self.config.prompt_mode == PromptMode::Dim && line.has_prompt(&self.config.prompt) {
+            color.scale_alpha(0.45)
+        } else {
+            color
+        }
+    }
+
+    // Same lines as the terminal, larger and without controls
+    fn log_window_view(&self) -> Element<'_, Message> {
+        let options = self.render_options();
+        container(
+            scrollable(
+                column(
+                    self.terminal_output
+                        .iter()
+                        .map(|line| {
+                            text(line.render(&options))
+                                .size(22)
+                                .font(self.terminal_font())
+                                .color(self.line_color(line))
+                                .into()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .spacing(4)
+                .padding(20),
+            )
+            .anchor_bottom(),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn nav_button<'a>(&'a self, label: &'a str, window: WindowState) -> Element<'a, Message> {
+        let is_active =
+            std::mem::discriminant(&self.current_window) == std::mem::discriminant(&window);
+        let message = match window {
+            WindowState::Terminal => Message::ShowTerminal,
+            WindowState::Settings => Message::ShowSettings,
+            WindowState::Monitor => Message::ShowMonitor,
+            WindowState::FileView => Message::ShowFileView,
+            WindowState::Compare => Message::ShowCompare,
+        };
+        button(text(label).size(if is_active { 16 } else { 14 }))
+            .on_press(message)
+            .into()
+    }
+
+    fn terminal_view(&self) -> Element<'_, Message> {
+        let status_text = if self.port_settings.connected {
+            text(format!(
+                "✅ Подключен к {} ({})",
+                self.port_settings
+                    .port_name
+                    .as_ref()
+                    .unwrap_or(&"Unknown".to_string()),
+                self.connection_label()
+            ))
+            .size(14)
+        } else {
+            text("❌ Отключен").size(14)
+        };
+        let read_only_badge: Element<Message> = if self.port_settings.read_only {
+            container(text("👁 только чтение").size(14))
+                .padding([2, 8])
+                .style(container::rounded_box)
+                .into()
+        } else {
+            column![].into()
+        };
+        let raw_indicator: Element<Message> = if self.raw_mode {
+            container(text("⌨️ RAW").size(14))
+                .padding([2, 8])
+                .style(container::rounded_box)
+                .into()
+        } else {
+            column![].into()
+        };
+        let led = |label: &str, lit: bool, kind: LineKind| {
+            text(format!("● {}", label)).size(14).color(if lit {
+                self.config.line_colors.color(kind)
+            } else {
+                Color::from_rgb(0.35, 0.35, 0.35)
+            })
+        };
+        let sparkline: Element<Message> = match (
+            self.config.value_sparkline,
+            &self.sparkline_svg,
+            self.value_samples.back(),
+        ) {
+            (true, Some(handle), Some(latest)) => row![
+                svg(handle.clone())
+                    .width(traffic_chart::SPARKLINE_WIDTH as f32)
+                    .height(traffic_chart::SPARKLINE_HEIGHT as f32),
+                text(format!("{}", latest)).size(14),
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center)
+            .into(),
+            _ => column![].into(),
+        };
+        let status_row = row![
+            status_text,
+            led("RX", self.activity.rx, LineKind::Rx),
+            led("TX", self.activity.tx, LineKind::Tx),
+            sparkline,
+            raw_indicator,
+            read_only_badge,
+            horizontal_space(),
+            button(text("🔄 Reset").size(14))
+                .style(button::secondary)
+                .on_press_maybe(self.can_transmit().then_some(Message::ResetTarget)),
+            button(text("⛔ STOP").size(14))
+                .style(button::danger)
+                .on_press(Message::StopAll),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+        // Chips for ports that are gone stay visible but can't be clicked
+        let recent_row: Element<Message> =
+            if self.config.show_recent_ports && !self.config.recent_ports.is_empty() {
+                row(self.config.recent_ports.iter().map(|recent| {
+                    let present = self.available_ports.contains(&recent.port_name);
+                    button(
+                        text(format!(
+                            "{} @{} {}",
+                            port_label(&recent.port_name),
+                            recent.baud_rate,
+                            recent.framing
+                        ))
+                        .size(12),
+                    )
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (present && !self.port_settings.connected)
+                            .then(|| Message::ConnectRecent(recent.clone())),
+                    )
+                    .into()
+                }))
+                .spacing(5)
+                .into()
+            } else {
+                row![].into()
+            };
+
+        let render_options = self.render_options();
+        let matches = self.search_matches();
+        let current_match = matches.get(self.search_match).copied();
+        let search_row = row![
+            text_input("🔍 Найти...", &self.search_query)
+                .on_input(Message::SearchChanged)
+                .on_submit(Message::SearchNext)
+                .size(12)
+                .width(250),
+            button(text("▲").size(12))
+                .on_press_maybe((!matches.is_empty()).then_some(Message::SearchPrev)),
+            button(text("▼").size(12))
+                .on_press_maybe((!matches.is_empty()).then_some(Message::SearchNext)),
+            text(if self.search_query.is_empty() {
+                String::new()
+            } else if matches.is_empty() {
+                "нет совпадений".to_string()
+            } else {
+                format!("{} из {}", self.search_match + 1, matches.len())
+            })
+            .size(12),
+            horizontal_space(),
+            text_input("заметка в лог", &self.marker_input)
+                .on_input(Message::MarkerChanged)
+                .on_submit(Message::InsertMarker)
+                .size(12)
+                .width(200),
+            button(text("📌 Метка").size(12)).on_press(Message::InsertMarker),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+        let flashing = self.flash_until.is_some_and(|until| Instant::now() < until);
+        let alert_color = self.config.line_colors.color(LineKind::Alert);
+        let font = self.terminal_font();
+        let tx_color = self.config.line_colors.color(LineKind::Tx);
+        // Dimmed until the frame delimiter turns it into a regular line
+        let partial_line = (self.show_partial && !self.frame_buffer.is_empty()).then(|| {
+            text(format!(
+                "<- {}…",
+                String::from_utf8_lossy(&self.frame_buffer)
+            ))
+            .size(12)
+            .color(self.config.line_colors.color(LineKind::Rx).scale_alpha(0.5))
+        });
+        let terminal_display = container(
+            scrollable(
+                column(
+                    self.terminal_output
+                        .iter()
+                        .enumerate()
+                        .map(|(i, line)| {
+                            let rendered = line.render(&render_options);
+                            let color = self.line_color(line);
+                            let line_text: Element<Message> = if self.config.ansi_colors
+                                && line.kind == LineKind::Rx
+                                && self.display_mode == DisplayMode::Text
+                            {
+                                let spans: Vec<_> = ansi::parse(&rendered)
+                                    .into_iter()
+                                    .map(|segment| {
+                                        span(segment.text).color(segment.color.unwrap_or(color))
+                                    })
+                                    .collect();
+                                rich_text(spans).size(12).font(font).into()
+                            } else {
+                                text(rendered).size(12).font(font).color(color).into()
+                            };
+                            let line_text: Element<Message> =
+                                match (&line.port, self.config.tag_ports) {
+                                    (Some(port), true) => row![
+                                        text(format!("[{}]", port_label(port)))
+                                            .size(12)
+                                            .color(self.port_color(port)),
+                                        line_text
+                                    ]
+                                    .spacing(5)
+                                    .into(),
+                                    _ => line_text,
+                                };
+                            // A bracket in the TX color ties each response to its request
+                            let line_text: Element<Message> =
+                                match (line.exchange, self.config.group_exchanges) {
+                                    (Some(_), true) => row![
+                                        text(if line.kind == LineKind::Tx {
+                                            "┌"
+                                        } else {
+                                            "│"
+                                        })
+                                        .size(12)
+                                        .font(font)
+                                        .color(tx_color),
+                                        line_text
+                                    ]
+                                    .spacing(5)
+                                    .into(),
+                                    _ => line_text,
+                                };
+                            let line_text: Element<Message> = if current_match == Some(i) {
+                                container(line_text)
+                                    .style(container::rounded_box)
+                                    .width(Length::Fill)
+                                    .into()
+                            } else {
+                                line_text
+                            };
+                            if !self.split_columns {
+                                return line_text;
+                            }
+                            let (left, right) = if line.kind == LineKind::Tx {
+                                (horizontal_space().into(), line_text)
+                            } else {
+                                (line_text, horizontal_space().into())
+                            };
+                            row![
+                                container(left).width(Length::FillPortion(1)),
+                                container(right).width(Length::FillPortion(1)),
+                            ]
+                            .spacing(10)
+                            .into()
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .push_maybe(partial_line)
+                .spacing(2)
+                .padding(10),
+            )
+            .id(scrollable::Id::new(TERMINAL_SCROLL_ID))
+            .on_scroll(Message::TerminalScrolled),
+        )
+        .style(move |_| {
+            let mut style = container::Style::default();
+            if flashing {
+                style.border = iced::Border {
+                    color: alert_color,
+                    width: 3.0,
+                    radius: 4.0.into(),
+                };
+            }
+            style
+        })
+        .height(Length::FillPortion(3))
+        .width(Length::Fill);
+
+        let input_invalid = self.hex_error.is_some();
+        let input_locked = self.raw_mode || self.port_settings.read_only;
+        let placeholder = if self.port_settings.read_only {
+            "Только чтение: отправка отключена"
+        } else if self.raw_mode {
+            "RAW режим: клавиши отправляются сразу"
+        } else if self.hex_mode {
+            "HEX: 01 A0 FF..."
+        } else {
+            "Введите команду..."
+        };
+        let input_field: Element<Message> = if self.config.enter_sends {
+            text_input(placeholder, &self.input_text)
+                .id(text_input::Id::new(COMMAND_INPUT_ID))
+                .on_input_maybe((!input_locked).then_some(Message::InputChanged))
+                .on_submit_maybe((!input_locked).then_some(Message::SendData))
+                .style(move |theme, status| {
+                    let mut style = text_input::default(theme, status);
+                    if input_invalid {
+                        style.border.color = Color::from_rgb(0.9, 0.2, 0.2);
+                        style.border.width = 2.0;
+                    }
+                    style
+                })
+                .width(Length::FillPortion(4))
+                .into()
+        } else {
+            let editor = text_editor(&self.multiline_input).placeholder(placeholder);
+            // Without an action handler the editor is read-only, as in RAW mode
+            let editor = if input_locked {
+                editor
+            } else {
+                editor.on_action(Message::MultilineEdited)
+            };
+            container(
+                editor
+                    .key_binding(|press| {
+                        if matches!(press.key, Key::Named(key::Named::Enter))
+                            && press.modifiers.command()
+                        {
+                            Some(text_editor::Binding::Custom(Message::SendData))
+                        } else {
+                            text_editor::Binding::from_key_press(press)
+                        }
+                    })
+                    .style(move |theme, status| {
+                        let mut style = text_editor::default(theme, status);
+                        if input_invalid {
+                            style.border.color = Color::from_rgb(0.9, 0.2, 0.2);
+                            style.border.width = 2.0;
+                        }
+                        style
+                    })
+                    .height(100),
+            )
+            .width(Length::FillPortion(4))
+            .into()
+        };
+        let input_row = row![
+            input_field,
+            button("Отправить")
+                .on_press_maybe((!input_invalid && !input_locked).then_some(Message::SendData))
+                .width(Length::FillPortion(1)),
+            button("✖")
+                .style(button::secondary)
+                .on_press_maybe((!self.input_text.is_empty()).then_some(Message::ClearInput)),
+            button("📋 Вставить и отправить").on_press_maybe(
+                (self.can_transmit() && !self.raw_mode).then_some(Message::PasteAndSend)
+            ),
+            checkbox("HEX", self.hex_mode).on_toggle(Message::HexModeToggled),
+            checkbox("RAW", self.raw_mode).on_toggle(Message::RawModeToggled),
+        ]
+        .spacing(10)
+        .padding(10);
+
+        let tx_inspector: Element<Message> = if self.last_tx_frame.is_empty() {
+            column![].into()
+        } else {
+            column![
+                text(format!(
+                    "🔍 Последняя отправленная команда: {} байт",
+                    self.last_tx_frame.len()
+                ))
+                .size(12),
+                column(
+                    inspect_bytes(&self.last_tx_frame, INSPECTOR_MAX_BYTES)
+                        .into_iter()
+                        .map(|line| text(line).size(11).font(Font::MONOSPACE).into())
+                        .collect::<Vec<_>>(),
+                ),
+            ]
+            .spacing(2)
+            .padding([0, 10])
+            .into()
+        };
+
+        let hex_error_text: Element<Message> = match &self.hex_error {
+            Some(e) => text(format!("⚠️ {}", e))
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.2, 0.2))
+                .into(),
+            None => column![].into(),
+        };
+
+        let send_options = row![
+            text("Окончание строки:").size(12),
+            pick_list(
+                &LineEnding::ALL[..],
+                Some(self.line_ending),
+                Message::LineEndingSelected
+            )
+            .text_size(12),
+            text("Префикс:").size(12),
+            text_input("\\x02", &self.send_prefix_input)
+                .on_input(Message::SendPrefixChanged)
+                .size(12)
+                .width(90),
+            text("Суффикс:").size(12),
+            text_input("; или \\x03", &self.send_suffix_input)
+                .on_input(Message::SendSuffixChanged)
+                .size(12)
+                .width(90),
+            pick_list(
+                &SendChecksum::ALL[..],
+                Some(self.config.send_checksum),
+                Message::SendChecksumSelected
+            )
+            .text_size(12),
+            text(
+                parse_escapes(&self.send_prefix_input)
+                    .and(parse_escapes(&self.send_suffix_input))
+                    .err()
+                    .unwrap_or_default()
+            )
+            .size(12)
+            .color(self.config.line_colors.color(LineKind::Error)),
+            checkbox("Обрезать пробелы", self.trim_on_send)
+                .on_toggle_maybe((!self.hex_mode).then_some(Message::TrimOnSendToggled))
+                .size(14)
+                .text_size(12),
+            checkbox("Очищать после отправки", self.clear_input_on_send)
+                .on_toggle(Message::ClearInputOnSendToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("Оставлять фокус в поле", self.config.keep_input_focus)
+                .on_toggle(Message::KeepFocusToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("Enter отправляет", self.config.enter_sends)
+                .on_toggle(Message::EnterSendsToggled)
+                .size(14)
+                .text_size(12),
+            text(if self.config.enter_sends {
+                ""
+            } else {
+                "Ctrl+Enter — отправить"
+            })
+            .size(12),
+        ]
+        .spacing(10)
+        .padding([0, 10])
+        .align_y(iced::Alignment::Center);
+
+        let line_send_row = row![
+            checkbox("Отправлять построчно", self.send_per_line)
+                .on_toggle(Message::SendPerLineToggled)
+                .size(14)
+                .text_size(12),
+            text("пауза, мс:").size(12),
+            text_input("0", &self.line_delay_input)
+                .on_input(Message::LineDelayChanged)
+                .size(12)
+                .width(70),
+        ]
+        .push_maybe(self.line_queue.as_ref().map(|queue| {
+            row![
+                text(format!(
+                    "📨 строка {} из {}",
+                    queue.index,
+                    queue.payloads.len()
+                ))
+                .size(12),
+                progress_bar(0.0..=queue.payloads.len() as f32, queue.index as f32)
+                    .width(150)
+                    .height(8),
+                button(text("⏹ Прервать").size(12)).on_press(Message::AbortLineQueue),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center)
+        }))
+        .spacing(10)
+        .padding([0, 10])
+        .align_y(iced::Alignment::Center);
+
+        let fill = parse_fill(&self.fill_byte_input, &self.fill_count_input);
+        let fill_row = row![
+            text("Заполнение:").size(12),
+            text_input("байт", &self.fill_byte_input)
+                .on_input(Message::FillByteChanged)
+                .size(12)
+                .width(70),
+            text("×").size(12),
+            text_input("кол-во", &self.fill_count_input)
+                .on_input(Message::FillCountChanged)
+                .size(12)
+                .width(80),
+            button(text("Отправить блок").size(12))
+                .on_press_maybe((fill.is_ok() && self.can_transmit()).then_some(Message::SendFill)),
+            match &fill {
+                Ok((_, count)) => text(format!("{} байт в очереди", count)).size(12),
+                Err(e) => text(e.clone())
+                    .size(12)
+                    .color(self.config.line_colors.color(LineKind::Error)),
+            },
+        ]
+        .spacing(10)
+        .padding([0, 10])
+        .align_y(iced::Alignment::Center);
+
+        let byte_list = parse_byte_list(&self.bytes_input);
+        let bytes_row = row![
+            text("Байты:").size(12),
+            text_input("255 0xFF 0b1010 ...", &self.bytes_input)
+                .on_input(Message::BytesInputChanged)
+                .on_submit(Message::SendBytes)
+                .size(12),
+            button(text("Отправить байты").size(12)).on_press_maybe(
+                (byte_list.is_ok() && self.can_transmit()).then_some(Message::SendBytes)
+            ),
+            match &byte_list {
+                Ok(bytes) => text(format!("{} байт", bytes.len())).size(12),
+                Err(_) if self.bytes_input.trim().is_empty() => text("").size(12),
+                Err(e) => text(e.clone())
+                    .size(12)
+                    .color(self.config.line_colors.color(LineKind::Error)),
+            },
+        ]
+        .spacing(10)
+        .padding([0, 10])
+        .align_y(iced::Alignment::Center);
+
+        let interval = parse_interval(&self.repeat_interval_input);
+        let repeat_row = row![
+            text("Цикл:").size(12),
+            text_input("команда1; команда2; ...", &self.repeat_commands_input)
+                .on_input(Message::RepeatCommandsChanged)
+                .size(12),
+            text_input("мс", &self.repeat_interval_input)
+                .on_input(Message::RepeatIntervalChanged)
+                .size(12)
+                .width(70),
+            text("мс").size(12),
+            if self.repeat_running {
+                button(text("⏹ Стоп").size(12))
+                    .style(button::danger)
+                    .on_press(Message::RepeatToggled)
+            } else {
+                button(text("🔁 Старт").size(12)).on_press_maybe(
+                    (interval.is_ok() && self.can_transmit()).then_some(Message::RepeatToggled),
+                )
+            },
+            match &interval {
+                Ok(_) if self.repeat_running =>
+                    text(format!("следующая: {}", self.repeat_index + 1)).size(12),
+                Ok(_) => text("").size(12),
+                Err(e) => text(e.clone())
+                    .size(12)
+                    .color(self.config.line_colors.color(LineKind::Error)),
+            },
+        ]
+        .spacing(10)
+        .padding([0, 10])
+        .align_y(iced::Alignment::Center);
+
+        let clear_controls: Element<Message> = if self.clear_armed {
+            row![
+                button(text(format!(
+                    "⚠️ Удалить {} несохраненных строк?",
+                    self.terminal_output.len()
+                )))
+                .style(button::danger)
+                .on_press(Message::ClearTerminal),
+                button("Отмена")
+                    .style(button::secondary)
+                    .on_press(Message::CancelClear),
+            ]
+            .spacing(5)
+            .into()
+        } else if self
+            .clear_backup
+            .as_ref()
+            .is_some_and(ClearBackup::is_fresh)
+        {
+            row![
+                button("Очистить").on_press(Message::ClearTerminal),
+                button("↩️ Отменить очистку")
+                    .style(button::secondary)
+                    .on_press(Message::UndoClear),
+            ]
+            .spacing(5)
+            .into()
+        } else {
+            button("Очистить").on_press(Message::ClearTerminal).into()
+        };
+        // Only meaningful while the hexdump is shown
+        let hexdump_width_picker: Element<Message> = if self.display_mode == DisplayMode::Hexdump {
+            pick_list(
+                HEXDUMP_WIDTHS,
+                Some(hexdump_width(self.config.hexdump_width)),
+                Message::HexdumpWidthSelected,
+            )
+            .text_size(12)
+            .into()
+        } else {
+            column![].into()
+        };
+        let controls = row![
+            clear_controls,
+            button(if self.log_window.is_some() {
+                "🪟 Закрыть окно лога"
+            } else {
+                "🪟 Окно лога"
+            })
+            .on_press(Message::ToggleLogWindow),
+            text(format!(
+                "Отправлено: {} байт | Получено: {} байт",
+                self.sent_bytes, self.received_bytes
+            ))
+            .size(12),
+            self.tx_queue_indicator(),
+            checkbox("Автопрокрутка", self.config.follow_output)
+                .on_toggle(Message::FollowOutputToggled)
+                .size(14)
+                .text_size(12),
+            button(text("⬇ Вниз").size(12))
+                .style(button::secondary)
+                .on_press_maybe(
+                    (!self.following || !self.config.follow_output)
+                        .then_some(Message::JumpToBottom)
+                ),
+            checkbox("Сворачивать повторы", self.suppress_repeats)
+                .on_toggle(Message::SuppressRepeatsToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("Время", self.show_timestamps)
+                .on_toggle(Message::TimestampsToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("только в начале строки", self.config.line_timestamps)
+                .on_toggle_maybe(
+                    self.show_timestamps
+                        .then_some(Message::LineTimestampsToggled)
+                )
+                .size(14)
+                .text_size(12),
+            checkbox("Δt", self.show_deltas)
+                .on_toggle(Message::DeltasToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("TX отдельной колонкой", self.split_columns)
+                .on_toggle(Message::SplitColumnsToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("␍␊", self.show_eol)
+                .on_toggle(Message::EolGlyphsToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("HEX", self.display_mode == DisplayMode::Hex)
+                .on_toggle(Message::HexOutputToggled)
+                .size(14)
+                .text_size(12),
+            pick_list(
+                DisplayMode::ALL,
+                Some(self.display_mode),
+                Message::DisplayModeSelected
+            )
+            .text_size(12),
+            hexdump_width_picker,
+            checkbox("ANSI-цвета", self.config.ansi_colors)
+                .on_toggle(Message::AnsiColorsToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("Моноширинный шрифт", self.config.monospace_terminal)
+                .on_toggle(Message::MonospaceToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("Тренд значений", self.config.value_sparkline)
+                .on_toggle(Message::ValueSparklineToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("Группировать запрос/ответ", self.config.group_exchanges)
+                .on_toggle(Message::GroupExchangesToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("CR перезаписывает строку", self.cr_overwrite)
+                .on_toggle(Message::CrOverwriteToggled)
+                .size(14)
+                .text_size(12),
+            checkbox("Незавершенный кадр", self.show_partial)
+                .on_toggle(Message::PartialLineToggled)
+                .size(14)
+                .text_size(12),
+        ]
+        .spacing(10)
+        .padding(10);
+
+        column![
+            status_row,
+            recent_row,
+            search_row,
+            terminal_display,
+            self.error_panel(),
+            input_row,
+            tx_inspector,
+            send_options,
+            line_send_row,
+            fill_row,
+            bytes_row,
+            repeat_row,
+            hex_error_text,
+            controls,
+        ]
+        .spacing(10)
+        .padding(20)
+        .into()
+    }
+
+    fn tx_queue_indicator(&self) -> Element<'_, Message> {
+        if self.tx_queued == 0 {
+            return column![].into();
+        }
+        let label = format!(
+            "TX в очереди: {} ({} байт)",
+            self.tx_queued, self.tx_queued_bytes
+        );
+        if self.tx_queued_bytes > TX_QUEUE_WARN_BYTES {
+            text(format!("⚠️ {} — устройство не успевает", label))
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.6, 0.1))
+                .into()
+        } else {
+            text(label).size(12).into()
+        }
+    }
+
+    // Collapsed to its header by default; the list shows the newest errors first
+    fn error_panel(&self) -> Element<'_, Message> {
+        let header = row![
+            button(
+                text(format!(
+                    "{} Ошибки ({})",
+                    if self.show_errors { "▾" } else { "▸" },
+                    self.errors.len()
+                ))
+                .size(12)
+            )
+            .style(button::secondary)
+            .on_press(Message::ToggleErrorPanel),
+            horizontal_space(),
+            button(text("Очистить ошибки").size(12))
+                .style(button::secondary)
+                .on_press_maybe((!self.errors.is_empty()).then_some(Message::ClearErrors)),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+        if !self.show_errors {
+            return container(header).padding([0, 10]).into();
+        }
+
+        let filter = self.error_filter.to_lowercase();
+        let color = self.config.line_colors.color(LineKind::Error);
+        let entries = column(
+            self.errors
+                .iter()
+                .rev()
+                .filter(|entry| filter.is_empty() || entry.text.to_lowercase().contains(&filter))
+                .map(|entry| {
+                    text(format!("[{}] {}", entry.time, entry.text))
+                        .size(12)
+                        .color(color)
+                        .into()
+                })
+                .collect::<Vec<_>>(),
+        )
+        .spacing(2);
+        column![
+            header,
+            text_input("🔍 Найти в ошибках...", &self.error_filter)
+                .on_input(Message::ErrorFilterChanged)
+                .size(12),
+            scrollable(entries).height(150),
+        ]
+        .spacing(5)
+        .padding([0, 10])
+        .into()
+    }
+
+    fn settings_view(&self) -> Element<'_, Message> {
+        let port_selection = column![
+            text("COM Порт:").size(16),
+            pick_list(
+                &self.available_ports[..],
+                self.port_settings.port_name.as_ref(),
+                Message::PortSelected,
+            ),
+            row![
+                button("🔄 Обновить список").on_press(Message::RefreshPorts),
+                button("🩺 Сведения о портах").on_press(Message::DumpPorts),
+            ]
+            .spacing(10),
+            checkbox("Автообновление списка (2 с)", self.auto_refresh_ports)
+                .on_toggle(Message::AutoRefreshToggled),
+            checkbox(
+                "Подключаться к последнему порту при запуске",
+                self.config.auto_connect
+            )
+            .on_toggle(Message::AutoConnectToggled),
+            checkbox(
+                "Недавние порты на вкладке терминала",
+                self.config.show_recent_ports
+            )
+            .on_toggle(Message::RecentPortsToggled),
+            row![
+                checkbox("Подключаться к USB VID:PID", self.config.pinned_usb_enabled)
+                    .on_toggle(Message::PinnedUsbToggled),
+                text_input("0403:6001", &self.pinned_usb_input)
+                    .on_input(Message::PinnedUsbChanged)
+                    .on_submit(Message::ApplyPinnedUsb)
+                    .width(100),
+                button("Применить").on_press(Message::ApplyPinnedUsb),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        ]
+        .spacing(10);
+
+        let baud_scan_panel: Element<Message> = match &self.baud_scan {
+            Some(scan) => {
+                let best = scan.best();
+                let mut rows: Vec<Element<Message>> = scan
+                    .results
+                    .iter()
+                    .map(|(rate, result)| {
+                        let line = match result {
+                            Ok(score) => format!(
+                                "{:>7}: {} байт, текст {:.0}%",
+                                rate,
+                                score.bytes,
+                                score.text * 100.0
+                            ),
+                            Err(e) => format!("{:>7}: {}", rate, e),
+                        };
+                        let line = text(line).size(12).font(Font::MONOSPACE);
+                        if Some(*rate) == best {
+                            line.color(self.config.line_colors.color(LineKind::Rx))
+                                .into()
+                        } else {
+                            line.into()
+                        }
+                    })
+                    .collect();
+                rows.push(if scan.running {
+                    text(format!(
+                        "проверено {} из {}",
+                        scan.results.len(),
+                        SCAN_BAUD_RATES.len()
+                    ))
+                    .size(12)
+                    .into()
+                } else {
+                    row![
+                        match best {
+                            Some(rate) => button(text(format!("✅ Подключиться на {}", rate)))
+                                .on_press(Message::ApplyScannedBaud(rate)),
+                            None => button(text("Нет подходящей скорости")),
+                        },
+                        button("Закрыть")
+                            .style(button::secondary)
+                            .on_press(Message::CloseBaudScan),
+                    ]
+                    .spacing(10)
+                    .into()
+                });
+                column(rows).spacing(2).into()
+            }
+            None => button("🔎 Подобрать скорость")
+                .on_press_maybe(
+                    (!self.port_settings.connected && self.port_settings.port_name.is_some())
+                        .then_some(Message::ScanBaud),
+                )
+                .into(),
+        };
+
+        let baud_selection = column![
+            text("Скорость (baud):").size(16),
+            pick_list(
+                &self.baud_rates[..],
+                Some(self.port_settings.baud_rate),
+                Message::BaudRateSelected,
+            ),
+            row![
+                text_input("своя скорость", &self.custom_baud_input)
+                    .on_input(Message::CustomBaudChanged)
+                    .on_submit(Message::ApplyCustomBaud)
+                    .width(140),
+                button("Применить").on_press(Message::ApplyCustomBaud),
+            ]
+            .spacing(10),
+            baud_scan_panel,
+        ]
+        .spacing(10);
+
+        let connection_controls = if self.port_settings.connected {
+            row![
+                // Closing mid-transfer would cut the file off; STOP cancels it first
+                button("🔌 Отключиться").on_press_maybe(
+                    self.file_transfer
+                        .is_none()
+                        .then_some(Message::DisconnectPort)
+                ),
+                button("🔁 Переподключить")
+                    .on_press_maybe(self.file_transfer.is_none().then_some(Message::Reopen)),
+            ]
+            .spacing(10)
+        } else {
+            row![
+                button("🔌 Подключиться").on_press(Message::ConnectPort),
+                button("⚡ К первому порту").on_press(Message::QuickConnect),
+            ]
+            .spacing(10)
+        };
+
+        let additional_settings = container(
+            column![
+                text("Параметры соединения:").size(16),
+                row(FRAMING_PRESETS
+                    .iter()
+                    .map(|&(data_bits, parity, stop_bits)| {
+                        let active = data_bits == self.port_settings.data_bits
+                            && parity == self.port_settings.parity
+                            && stop_bits == self.port_settings.stop_bits;
+                        button(text(framing_label(data_bits, parity, stop_bits)).size(14))
+                            .style(if active {
+                                button::primary
+                            } else {
+                                button::secondary
+                            })
+                            .on_press(Message::FramingPreset(data_bits, parity, stop_bits))
+                            .into()
+                    }))
+                .spacing(5),
+                row![
+                    text("Биты данных:").size(14),
+                    pick_list(
+                        DATA_BITS,
+                        Some(self.port_settings.data_bits),
+                        Message::DataBitsSelected
+                    ),
+                    text("Четность:").size(14),
+                    pick_list(
+                        PARITIES,
+                        Some(self.port_settings.parity),
+                        Message::ParitySelected
+                    ),
+                    text("Стоп-биты:").size(14),
+                    pick_list(
+                        STOP_BITS,
+                        Some(self.port_settings.stop_bits),
+                        Message::StopBitsSelected
+                    ),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Управление потоком:").size(14),
+                    pick_list(
+                        FLOW_CONTROLS,
+                        Some(self.port_settings.flow_control),
+                        Message::FlowControlSelected
+                    ),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                tooltip(
+                    checkbox("Монопольный доступ", self.port_settings.exclusive)
+                        .on_toggle(Message::ExclusiveToggled),
+                    container(
+                        text(
+                            "Linux/macOS: снятие флага позволяет другим программам открыть тот же порт.\n\
+                             Windows: COM-порты всегда открываются монопольно, совместный режим недоступен."
+                        )
+                        .size(12)
+                    )
+                    .padding(8)
+                    .style(container::rounded_box),
+                    tooltip::Position::Bottom,
+                ),
+                checkbox("Только чтение (отправка отключена)", self.port_settings.read_only)
+                    .on_toggle(Message::ReadOnlyToggled),
+                row![
+                    checkbox("DTR", self.dtr).on_toggle_maybe(
+                        self.port_settings.connected.then_some(Message::DtrToggled)
+                    ),
+                    checkbox("RTS", self.rts).on_toggle_maybe(
+                        self.port_settings.connected.then_some(Message::RtsToggled)
+                    ),
+                    text("Импульс сброса:").size(14),
+                    text_input("мс", &self.reset_pulse_input)
+                        .on_input(Message::ResetPulseChanged)
+                        .width(60),
+                    text("мс").size(14),
+                    checkbox("вместе с RTS", self.reset_uses_rts)
+                        .on_toggle(Message::ResetUsesRtsToggled),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Интервал опроса порта (1..1000 мс):").size(14),
+                    text_input("100", &self.poll_interval_input)
+                        .on_input(Message::PollIntervalChanged)
+                        .on_submit(Message::ApplyPollInterval)
+                        .width(80),
+                    button("Применить").on_press(Message::ApplyPollInterval),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Разделитель кадров (байт или none):").size(14),
+                    text_input("none", &self.frame_delimiter_input)
+                        .on_input(Message::FrameDelimiterChanged)
+                        .on_submit(Message::ApplyFrameDelimiter)
+                        .width(80),
+                    button("Применить").on_press(Message::ApplyFrameDelimiter),
+                    text(format!(
+                        "сейчас: {}",
+                        delimiter_label(self.config.frame_delimiter)
+                    ))
+                    .size(14),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Автоотключение при простое (мин, 0 = выкл):").size(14),
+                    text_input("0", &self.idle_timeout_input)
+                        .on_input(Message::IdleTimeoutChanged)
+                        .on_submit(Message::ApplyIdleTimeout)
+                        .width(80),
+                    button("Применить").on_press(Message::ApplyIdleTimeout),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Повторы подключения:").size(14),
+                    text_input("0", &self.connect_retries_input)
+                        .on_input(Message::ConnectRetriesChanged)
+                        .on_submit(Message::ApplyConnectRetry)
+                        .width(60),
+                    text("с паузой").size(14),
+                    text_input("500", &self.connect_retry_delay_input)
+                        .on_input(Message::ConnectRetryDelayChanged)
+                        .on_submit(Message::ApplyConnectRetry)
+                        .width(80),
+                    text("мс").size(14),
+                    button("Применить").on_press(Message::ApplyConnectRetry),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    checkbox(
+                        "Переподключаться при потере связи во время отправки",
+                        self.config.reconnect_on_send_error
+                    )
+                    .on_toggle(Message::ReconnectOnSendErrorToggled),
+                    checkbox(
+                        "повторить неотправленное",
+                        self.config.resend_after_reconnect
+                    )
+                    .on_toggle_maybe(
+                        self.config
+                            .reconnect_on_send_error
+                            .then_some(Message::ResendAfterReconnectToggled)
+                    ),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Подтверждать отправку файлов больше (КБ, 0 = выкл):").size(14),
+                    text_input("1024", &self.large_file_input)
+                        .on_input(Message::LargeFileChanged)
+                        .on_submit(Message::ApplyLargeFile)
+                        .width(80),
+                    button("Применить").on_press(Message::ApplyLargeFile),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+            ]
+            .spacing(5),
+        )
+        .padding(15);
+
+        let color_row = |kind: LineKind| {
+            let swatch_color = parse_hex_color(self.color_inputs.get(kind)).ok();
+            row![
+                text(format!("{}:", kind)).size(14).width(80),
+                text_input("#RRGGBB", self.color_inputs.get(kind))
+                    .on_input(move |value| Message::ColorInputChanged(kind, value))
+                    .on_submit(Message::ApplyColors)
+                    .width(120),
+                text("■■■")
+                    .size(16)
+                    .color(swatch_color.unwrap_or(Color::TRANSPARENT)),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center)
+        };
+        let color_settings = container(
+            column![
+                text("Цвета терминала:").size(16),
+                color_row(LineKind::Rx),
+                color_row(LineKind::Tx),
+                color_row(LineKind::Info),
+                color_row(LineKind::Error),
+                color_row(LineKind::Alert),
+                row![
+                    button("Применить").on_press(Message::ApplyColors),
+                    button("По умолчанию").on_press(Message::ResetColors),
+                ]
+                .spacing(10),
+                row![
+                    checkbox("Метки портов", self.config.tag_ports)
+                        .on_toggle(Message::TagPortsToggled),
+                    text(match &self.port_settings.port_name {
+                        Some(port) => format!("цвет {}:", port_label(port)),
+                        None => "порт не выбран".to_string(),
+                    })
+                    .size(14),
+                    text_input("#RRGGBB", &self.port_color_input)
+                        .on_input(Message::PortColorChanged)
+                        .on_submit(Message::ApplyPortColor)
+                        .width(120),
+                    button("Применить").on_press_maybe(
+                        self.port_settings
+                            .port_name
+                            .is_some()
+                            .then_some(Message::ApplyPortColor)
+                    ),
+                    text("■■■").size(16).color(
+                        self.port_settings
+                            .port_name
+                            .as_deref()
+                            .map_or(Color::TRANSPARENT, |port| self.port_color(port))
+                    ),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+            ]
+            .spacing(5),
+        )
+        .padding(15);
+
+        let max_len_error = parse_max_line_len(&self.max_line_len_input).err();
+        let display_settings = container(
+            column![
+                text("Отображение:").size(16),
+                row![
+                    text("Макс. длина строки:").size(14),
+                    text_input("символов", &self.max_line_len_input)
+                        .on_input(Message::MaxLineLenChanged)
+                        .on_submit(Message::ApplyMaxLineLen)
+                        .width(100),
+                    button("Применить").on_press_maybe(
+                        max_len_error.is_none().then_some(Message::ApplyMaxLineLen)
+                    ),
+                    text(max_len_error.unwrap_or_default())
+                        .size(12)
+                        .color(self.config.line_colors.color(LineKind::Error)),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Приглашение устройства:").size(14),
+                    text_input("> или $", &self.prompt_input)
+                        .on_input(Message::PromptChanged)
+                        .on_submit(Message::ApplyPrompt)
+                        .width(100),
+                    button("Применить").on_press(Message::ApplyPrompt),
+                    radio(
+                        "убирать",
+                        PromptMode::Strip,
+                        Some(self.config.prompt_mode),
+                        Message::PromptModeSelected
+                    )
+                    .size(14),
+                    radio(
+                        "приглушать",
+                        PromptMode::Dim,
+                        Some(self.config.prompt_mode),
+                        Message::PromptModeSelected
+                    )
+                    .size(14),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Непечатаемые байты:").size(14),
+                    radio(
+                        "показывать",
+                        RxFilter::Off,
+                        Some(self.config.rx_filter),
+                        Message::RxFilterSelected
+                    )
+                    .size(14),
+                    radio(
+                        "удалять",
+                        RxFilter::Drop,
+                        Some(self.config.rx_filter),
+                        Message::RxFilterSelected
+                    )
+                    .size(14),
+                    radio(
+                        "заменять на '.'",
+                        RxFilter::Replace,
+                        Some(self.config.rx_filter),
+                        Message::RxFilterSelected
+                    )
+                    .size(14),
+                    text("допустимо:").size(14),
+                    text_input("0x20-0x7E", &self.rx_filter_range_input)
+                        .on_input(Message::RxFilterRangeChanged)
+                        .on_submit(Message::ApplyRxFilterRange)
+                        .width(110),
+                    button("Применить").on_press(Message::ApplyRxFilterRange),
+                    text("+ TAB, CR, LF").size(12),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Байт NUL (0x00):").size(14),
+                    radio(
+                        "удалять",
+                        NulHandling::Strip,
+                        Some(self.config.nul_handling),
+                        Message::NulHandlingSelected
+                    )
+                    .size(14),
+                    radio(
+                        "показывать как <NUL>",
+                        NulHandling::Token,
+                        Some(self.config.nul_handling),
+                        Message::NulHandlingSelected
+                    )
+                    .size(14),
+                    radio(
+                        "оставлять",
+                        NulHandling::Keep,
+                        Some(self.config.nul_handling),
+                        Message::NulHandlingSelected
+                    )
+                    .size(14),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Тревога при тексте:").size(14),
+                    text_input("ERROR", &self.alert_pattern_input)
+                        .on_input(Message::AlertPatternChanged)
+                        .on_submit(Message::ApplyAlertPattern)
+                        .width(160),
+                    button("Применить").on_press(Message::ApplyAlertPattern),
+                    checkbox("звук", self.config.alert_sound).on_toggle(Message::AlertSoundToggled),
+                    checkbox("мигание", self.config.alert_flash)
+                        .on_toggle(Message::AlertFlashToggled),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Перенос RX каждые (символов, 0 = выкл):").size(14),
+                    text_input("0", &self.rx_wrap_input)
+                        .on_input(Message::RxWrapChanged)
+                        .on_submit(Message::ApplyRxWrap)
+                        .width(70),
+                    button("Применить").on_press(Message::ApplyRxWrap),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Окно ответа после отправки (мс, 0 = до следующей):").size(14),
+                    text_input("500", &self.exchange_window_input)
+                        .on_input(Message::ExchangeWindowChanged)
+                        .on_submit(Message::ApplyExchangeWindow)
+                        .width(70),
+                    button("Применить").on_press(Message::ApplyExchangeWindow),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Автосохранение лога: каждые").size(14),
+                    text_input("0", &self.autosave_kb_input)
+                        .on_input(Message::AutosaveKbChanged)
+                        .on_submit(Message::ApplyAutosave)
+                        .width(70),
+                    text("КБ или").size(14),
+                    text_input("0", &self.autosave_secs_input)
+                        .on_input(Message::AutosaveSecsChanged)
+                        .on_submit(Message::ApplyAutosave)
+                        .width(70),
+                    text("с (0 = выкл)").size(14),
+                    button("Применить").on_press(Message::ApplyAutosave),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+            ]
+            .spacing(5),
+        )
+        .padding(15);
+
+        let profiles = row![
+            text("Профиль:").size(16),
+            pick_list(
+                &self.config.profiles[..],
+                self.selected_profile.as_ref(),
+                Message::ProfileSelected,
+            )
+            .placeholder("нет профилей"),
+            text_input("имя профиля", &self.profile_name_input)
+                .on_input(Message::ProfileNameChanged)
+                .on_submit(Message::SaveProfile)
+                .width(180),
+            button("💾 Сохранить как профиль").on_press(Message::SaveProfile),
+            button("🗑 Удалить").on_press_maybe(
+                self.selected_profile
+                    .is_some()
+                    .then_some(Message::DeleteProfile)
+            ),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+        scrollable(
+            column![
+                text("Настройки COM порта").size(24),
+                profiles,
+                port_selection,
+                baud_selection,
+                connection_controls,
+                additional_settings,
+                color_settings,
+                display_settings,
+            ]
+            .spacing(20)
+            .padding(20),
+        )
+        .into()
+    }
+
+    fn monitor_view(&self) -> Element<'_, Message> {
+        let stats = container(
+            column![
+                text(format!("📤 Отправлено: {} байт", self.sent_bytes)).size(16),
+                text(format!("📥 Получено: {} байт", self.received_bytes)).size(16),
+                text(format!("📜 Строк в логе: {}", self.terminal_output.len())).size(14),
+                text(match self.framing_errors {
+                    Some(count) => format!("⚠️ Ошибок кадра/чётности: {}", count),
+                    None => "⚠️ Ошибки кадра/чётности: порт их не сообщает".to_string(),
+                })
+                .size(14)
+                .color_maybe(
+                    self.framing_errors
+                        .is_some_and(|count| count > 0)
+                        .then(|| self.config.line_colors.color(LineKind::Error))
+                ),
+                text(format!(
+                    "🔤 {} симв/с, 📃 {} строк/с",
+                    self.text_rates.0, self.text_rates.1
+                ))
+                .size(14),
+                text(format!(
+                    "📊 Мониторинг: {}",
+                    if self.monitoring {
+                        "🟢 Активен"
+                    } else {
+                        "🔴 Остановлен"
+                    }
+                ))
+                .size(16),
+                if self.port_settings.connected {
+                    text(format!(
+                        "🔗 Соединение: {} ({})",
+                        self.port_settings.port_name.as_ref().unwrap(),
+                        self.connection_label()
+                    ))
+                    .size(14)
+                } else {
+                    text("🔗 Соединение: Отключено").size(14)
+                },
+                text(format!(
+                    "⏱️ Uptime: {}",
+                    self.session
+                        .as_ref()
+                        .map(|session| session.started_at)
+                        .map(|t| format_uptime(t.elapsed()))
+                        .unwrap_or_else(|| "--:--:--".to_string())
+                ))
+                .size(14),
+            ]
+            .push_maybe(self.driver_queue.map(|queued| {
+                let warn = queued >= DRIVER_QUEUE_WARN;
+                text(format!("📦 В буфере драйвера: {} байт", queued))
+                    .size(14)
+                    .color_maybe(warn.then_some(Color::from_rgb(0.9, 0.2, 0.2)))
+            }))
+            .spacing(10),
+        )
+        .padding(20);
+
+        let controls = if self.monitoring {
+            button("⏹️ Остановить мониторинг").on_press(Message::StopMonitoring)
+        } else {
+            button("▶️ Начать мониторинг").on_press(Message::StartMonitoring)
+        };
+
+        let chart: Element<Message> = match &self.traffic_svg {
+            Some(handle) => svg(handle.clone()).width(Length::Fill).into(),
+            None => text("📈 Нет данных: график появится после подключения")
+                .size(14)
+                .into(),
+        };
+        let chart_panel = container(
+            column![
+                chart,
+                button("💾 Сохранить график")
+                    .on_press_maybe((!self.traffic.is_empty()).then_some(Message::SaveChart)),
+            ]
+            .spacing(10),
+        )
+        .padding(10)
+        .height(Length::FillPortion(2))
+        .width(Length::Fill);
+
+        let ping_panel = container(
+            column![
+                text("📶 Задержка (ping):").size(14),
+                row![
+                    text_input("запрос", &self.ping_request_input)
+                        .on_input(Message::PingRequestChanged)
+                        .width(120),
+                    text_input("ответ", &self.ping_response_input)
+                        .on_input(Message::PingResponseChanged)
+                        .width(120),
+                    text_input("раз", &self.ping_count_input)
+                        .on_input(Message::PingCountChanged)
+                        .width(60),
+                    match &self.ping {
+                        Some(run) => row![
+                            text(format!(
+                                "ответов: {}, таймаутов: {}",
+                                run.results.len(),
+                                run.timeouts
+                            ))
+                            .size(12),
+                            button("⏹ Стоп").on_press(Message::StopPing),
+                        ]
+                        .spacing(10)
+                        .align_y(iced::Alignment::Center),
+                        None => row![button("▶️ Ping")
+                            .on_press_maybe(self.can_transmit().then_some(Message::StartPing))],
+                    },
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+            ]
+            .spacing(5),
+        )
+        .padding(10);
+
+        let rx_inspector = container(
+            column![
+                text(format!(
+                    "🔍 Последний принятый блок: {} байт",
+                    self.last_rx_frame.len()
+                ))
+                .size(14),
+                column(
+                    inspect_bytes(&self.last_rx_frame, INSPECTOR_MAX_BYTES)
+                        .into_iter()
+                        .map(|line| text(line).size(12).font(Font::MONOSPACE).into())
+                        .collect::<Vec<_>>(),
+                ),
+            ]
+            .spacing(5),
+        )
+        .padding(10);
+
+        let capture_panel = container(match &self.capture {
+            Some(capture) if capture.is_complete() => column![
+                row![
+                    text(format!("📥 Захвачено {} байт", capture.data.len())).size(14),
+                    button("💾 Сохранить").on_press(Message::SaveCapture),
+                    button("Закрыть")
+                        .style(button::secondary)
+                        .on_press(Message::CancelCapture),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                column(
+                    inspect_bytes(&capture.data, INSPECTOR_MAX_BYTES)
+                        .into_iter()
+                        .map(|line| text(line).size(12).font(Font::MONOSPACE).into())
+                        .collect::<Vec<_>>(),
+                ),
+            ]
+            .spacing(5),
+            Some(capture) => column![
+                row![
+                    text(format!(
+                        "📥 Захват: {} / {} байт",
+                        capture.data.len(),
+                        capture.target
+                    ))
+                    .size(14),
+                    button("⏹ Отмена")
+                        .style(button::danger)
+                        .on_press(Message::CancelCapture),
+                ]
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+                progress_bar(0.0..=capture.target as f32, capture.data.len() as f32).height(10),
+            ]
+            .spacing(5),
+            None => column![row![
+                text("📥 Захватить байт:").size(14),
+                text_input("256", &self.capture_size_input)
+                    .on_input(Message::CaptureSizeChanged)
+                    .on_submit(Message::StartCapture)
+                    .width(100),
+                button("▶️ Начать").on_press(Message::StartCapture),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center)],
+        })
+        .padding(10);
+
+        column![
+            text("Мониторинг COM порта").size(24),
+            stats,
+            controls,
+            ping_panel,
+            rx_inspector,
+            capture_panel,
+            chart_panel,
+        ]
+        .spacing(20)
+        .padding(20)
+        .into()
+    }
+
+    fn file_view(&self) -> Element<'_, Message> {
+        let file_info = container(if let Some(path) = &self.log_file_path {
+            text(format!("📄 Текущий файл: {}", path)).size(14)
+        } else {
+            text("📄 Файл не выбран").size(14)
+        })
+        .padding(15);
+
+        let can_send_file = self.can_transmit() && self.file_transfer.is_none();
+        let file_controls = row![
+            button("🧮 Проверить файл").on_press(Message::VerifyFile),
+            button("💾 Сохранить лог").on_press(Message::SaveLog),
+            button("🧳 Сохранить сессию").on_press(Message::SaveSession),
+            button("📂 Открыть сессию").on_press(Message::LoadSession),
+            button("📤 Отправить (RAW)")
+                .on_press_maybe(can_send_file.then_some(Message::SendFile(TransferKind::Raw))),
+            checkbox("Файл в HEX", self.file_as_hex).on_toggle(Message::FileAsHexToggled),
+            if self.port_settings.flow_control == FlowControl::Software {
+                checkbox("Учитывать XON/XOFF", self.xon_xoff).on_toggle(Message::XonXoffToggled)
+            } else {
+                checkbox("Учитывать XON/XOFF", false)
+            },
+            button("📤 Отправить (ZMODEM)")
+                .on_press_maybe(can_send_file.then_some(Message::SendFile(TransferKind::Zmodem))),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+        let export_options = row![
+            text("Сохранять:").size(14),
+            radio(
+                "RX и TX",
+                ExportDirection::Both,
+                Some(self.export_direction),
+                Message::ExportDirectionSelected
+            )
+            .size(14),
+            radio(
+                "только RX",
+                ExportDirection::Rx,
+                Some(self.export_direction),
+                Message::ExportDirectionSelected
+            )
+            .size(14),
+            radio(
+                "только TX",
+                ExportDirection::Tx,
+                Some(self.export_direction),
+                Message::ExportDirectionSelected
+            )
+            .size(14),
+            text("|").size(14),
+            radio(
+                "текст",
+                ExportFormat::Text,
+                Some(self.export_format),
+                Message::ExportFormatSelected
+            )
+            .size(14),
+            radio(
+                "HEX",
+                ExportFormat::Hex,
+                Some(self.export_format),
+                Message::ExportFormatSelected
+            )
+            .size(14),
+            text("|").size(14),
+            radio(
+                "концы строк как есть",
+                SaveLineEnding::AsReceived,
+                Some(self.export_line_ending),
+                Message::ExportLineEndingSelected
+            )
+            .size(14),
+            radio(
+                "LF",
+                SaveLineEnding::Lf,
+                Some(self.export_line_ending),
+                Message::ExportLineEndingSelected
+            )
+            .size(14),
+            radio(
+                "CRLF",
+                SaveLineEnding::CrLf,
+                Some(self.export_line_ending),
+                Message::ExportLineEndingSelected
+            )
+            .size(14),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+        let script_controls: Element<Message> = match &self.script {
+            Some(run) => row![
+                text(format!(
+                    "📜 {}: шаг {} / {}",
+                    run.name,
+                    run.index + 1,
+                    run.steps.len()
+                ))
+                .size(14),
+                button("⏹ Стоп скрипта")
+                    .style(button::danger)
+                    .on_press(Message::StopScript),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center)
+            .into(),
+            None => button("📜 Запустить скрипт")
+                .on_press_maybe(can_send_file.then_some(Message::LoadScript))
+                .into(),
+        };
+
+        let replay_controls = row![
+            match &self.tx_recording {
+                Some((_, recording)) => button(text(format!(
+                    "⏹ Остановить запись TX ({})",
+                    recording.chunks.len()
+                )))
+                .style(button::danger)
+                .on_press(Message::StopTxRecording),
+                None => button("⏺ Запись TX").on_press(Message::StartTxRecording),
+            },
+            button("📂 Загрузить запись").on_press(Message::LoadRecording),
+            text(match (&self.replay, &self.loaded_recording) {
+                (Some(replay), _) => format!(
+                    "⏯ {}: {} / {}",
+                    replay.name,
+                    replay.index,
+                    replay.chunks.len()
+                ),
+                (None, Some((name, recording))) => format!(
+                    "{}: {} фрагментов, {}",
+                    name,
+                    recording.chunks.len(),
+                    format_uptime(recording.duration())
+                ),
+                (None, None) => "запись не загружена".to_string(),
+            })
+            .size(14),
+            text("скорость x").size(14),
+            text_input("1", &self.replay_speed_input)
+                .on_input(Message::ReplaySpeedChanged)
+                .on_submit(Message::StartReplay)
+                .width(60),
+            if self.replay.is_some() {
+                button("⏹ Стоп").on_press(Message::StopReplay)
+            } else {
+                button("▶ Воспроизвести").on_press_maybe(
+                    (self.can_transmit() && self.loaded_recording.is_some())
+                        .then_some(Message::StartReplay),
+                )
+            },
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+
+        #[cfg(feature = "scripting")]
+        let hook_name = self.byte_hook.as_ref().map(|hook| hook.name.as_str());
+        #[cfg(not(feature = "scripting"))]
+        let hook_name: Option<&str> = None;
+        let hook_controls: Element<Message> = match hook_name {
+            Some(name) => row![
+                text(format!("🧩 Обработчик байтов: {}", name)).size(14),
+                button("Отключить")
+                    .style(button::secondary)
+                    .on_press(Message::UnloadByteHook),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center)
+            .into(),
+            None => tooltip(
+                button("🧩 Обработчик байтов (rhai)").on_press(Message::LoadByteHook),
+                container(
+                    text(
+                        "Скрипт с функциями on_send(bytes) и on_recv(bytes),\n\
+                         которые возвращают измененные байты",
+                    )
+                    .size(12),
+                )
+                .padding(8)
+                .style(container::rounded_box),
+                tooltip::Position::Bottom,
+            )
+            .into(),
+        };
+
+        let transfer_prompt: Element<Message> = match &self.pending_transfer {
+            Some((kind, name, data)) => row![
+                text(format!(
+                    "⚠️ {}: {} — {} байт, примерно {} при {} baud ({} бит/байт). Отправить?",
+                    kind,
+                    name,
+                    data.len(),
+                    format_uptime(self.transfer_estimate(data.len())),
+                    self.actual_baud.unwrap_or(self.port_settings.baud_rate),
+                    self.port_settings.bits_per_byte()
+                ))
+                .size(14),
+                button("📤 Отправить")
+                    .style(button::danger)
+                    .on_press_maybe(can_send_file.then_some(Message::ConfirmLargeTransfer)),
+                button("Отмена")
+                    .style(button::secondary)
+                    .on_press(Message::CancelLargeTransfer),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center)
+            .into(),
+            None => column![].into(),
+        };
+
+        let transfer_status: Element<Message> = match &self.file_transfer {
+            Some(transfer) => column![
+                text(format!(
+                    "📤 {}: {} / {} байт, осталось ~{}{}",
+                    transfer.file_name,
+                    transfer.sent,
+                    transfer.total,
+                    format_uptime(
+                        self.transfer_estimate(transfer.total.saturating_sub(transfer.sent))
+                    ),
+                    if transfer.paused {
+                        " — ⏸ устройство прислало XOFF"
+                    } else {
+                        ""
+                    }
+                ))
+                .size(14),
+                progress_bar(0.0..=transfer.total.max(1) as f32, transfer.sent as f32).height(10),
+            ]
+            .spacing(5)
+            .into(),
+            None => column![].into(),
+        };
+
+        let session_history = container(scrollable(
+            column(
+                self.session_history
+                    .iter()
+                    .rev()
+                    .map(|record| {
+                        text(format!("{} — {}", record.started, record.summary()))
+                            .size(12)
+                            .into()
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .spacing(2)
+            .padding(10),
+        ))
+        .height(Length::FillPortion(1))
+        .width(Length::Fill);
+
+        let log_preview = container(scrollable(
+            column(
+                self.terminal_output
+                    .iter()
+                    .take(10)
+                    .map(|line| text(line.display(self.config.max_line_len)).size(12).into())
+                    .collect::<Vec<_>>(),
+            )
+            .spacing(2)
+            .padding(10),
+        ))
+        .height(Length::FillPortion(2))
+        .width(Length::Fill);
+
+        let verify_panel: Element<Message> = match &self.verified_file {
+            Some(file) => {
+                let expected = parse_crc(&self.expected_crc_input);
+                let verdict = match (file.crc, expected) {
+                    (Some(crc), Some(expected)) if crc == expected => "✅ совпадает",
+                    (Some(_), Some(_)) => "❌ НЕ совпадает",
+                    (Some(_), None) => "отправка после проверки CRC",
+                    (None, _) => "",
+                };
+                let status: Element<Message> = match file.crc {
+                    Some(crc) => text(format!(
+                        "🧮 {}: {} байт, CRC-32 {:08X}",
+                        file.name,
+                        file.data.len(),
+                        crc
+                    ))
+                    .size(14)
+                    .into(),
+                    None => column![
+                        text(format!("🧮 {}: вычисление CRC-32...", file.name)).size(14),
+                        progress_bar(0.0..=file.data.len().max(1) as f32, file.hashed as f32)
+                            .height(10),
+                    ]
+                    .spacing(5)
+                    .into(),
+                };
+                column![
+                    status,
+                    row![
+                        text("Ожидаемый CRC-32:").size(14),
+                        text_input("1A2B3C4D", &self.expected_crc_input)
+                            .on_input(Message::ExpectedCrcChanged)
+                            .width(120),
+                        text(verdict).size(14),
+                        button("📤 Отправить проверенный файл").on_press_maybe(
+                            (can_send_file && self.verified_crc_matches())
+                                .then_some(Message::SendVerified)
+                        ),
+                    ]
+                    .spacing(10)
+                    .align_y(iced::Alignment::Center),
+                ]
+                .spacing(5)
+                .into()
+            }
+            None => column![].into(),
+        };
+
+        let loaded_session: Element<Message> = match &self.loaded_session {
+            Some((name, snapshot)) => column![
+                row![
+                    text(format!(
+                        "📂 {}: {} @{} {}, RX {} / TX {} байт, длительность {}",
+                        name,
+                        snapshot.port_name.as_deref().unwrap_or("—"),
+                        snapshot.baud_rate,
+                        snapshot.framing,
+                        snapshot.received_bytes,
+                        snapshot.sent_bytes,
+                        snapshot
+                            .session_duration_secs
+                            .map(|secs| format_uptime(Duration::from_secs_f64(secs)))
+                            .unwrap_or_else(|| "--:--:--".to_string())
+                    ))
+                    .size(14),
+                    horizontal_space(),
+                    button("✖ Закрыть").on_press(Message::CloseLoadedSession),
+                ]
+                .align_y(iced::Alignment::Center),
+                container(scrollable(
+                    column(
+                        snapshot
+                            .lines
+                            .iter()
+                            .map(|line| text(line.render()).size(12).into())
+                            .collect::<Vec<_>>(),
+                    )
+                    .spacing(2)
+                    .padding(10),
+                ))
+                .style(container::rounded_box)
+                .height(Length::FillPortion(2))
+                .width(Length::Fill),
+            ]
+            .spacing(10)
+            .into(),
+            None => column![].into(),
+        };
+
+        column![
+            text("Работа с файлами").size(24),
+            file_info,
+            file_controls,
+            export_options,
+            transfer_prompt,
+            transfer_status,
+            verify_panel,
+            script_controls,
+            replay_controls,
+            hook_controls,
+            loaded_session,
+            text("Предварительный просмотр лога:").size(16),
+            log_preview,
+            text("История сессий:").size(16),
+            session_history,
+        ]
+        .spacing(20)
+        .padding(20)
+        .into()
+    }
+
+    // Unified diff of two saved logs; works without a connection
+    fn compare_view(&self) -> Element<'_, Message> {
+        let picker = |side: usize, label: &'static str| {
+            row![
+                button(label).on_press(Message::LoadCompareLog(side)),
+                text(match &self.compare_logs[side] {
+                    Some((name, lines)) => format!("{} ({} строк)", name, lines.len()),
+                    None => "файл не выбран".to_string(),
+                })
+                .size(14),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center)
+        };
+        let added_color = Color::from_rgb(0.3, 0.75, 0.35);
+        let removed_color = Color::from_rgb(0.9, 0.3, 0.3);
+
+        let result: Element<Message> = match &self.compare_diff {
+            Some(Ok(lines)) => {
+                let (added, removed) = lines.iter().fold((0, 0), |(a, r), line| match line {
+                    line_diff::DiffLine::Added(_) => (a + 1, r),
+                    line_diff::DiffLine::Removed(_) => (a, r + 1),
+                    line_diff::DiffLine::Same(_) => (a, r),
+                });
+                column![
+                    text(if added == 0 && removed == 0 {
+                        "Файлы совпадают".to_string()
+                    } else {
+                        format!("+{} / -{} строк", added, removed)
+                    })
+                    .size(14),
+                    container(scrollable(
+                        column(
+                            lines
+                                .iter()
+                                .map(|line| {
+                                    let (marker, body, color) = match line {
+                                        line_diff::DiffLine::Same(body) => (' ', body, None),
+                                        line_diff::DiffLine::Added(body) => {
+                                            ('+', body, Some(added_color))
+                                        }
+                                        line_diff::DiffLine::Removed(body) => {
+                                            ('-', body, Some(removed_color))
+                                        }
+                                    };
+                                    text(format!("{} {}", marker, body))
+                                        .size(12)
+                                        .font(Font::MONOSPACE)
+                                        .color_maybe(color)
+                                        .into()
+                                })
+                                .collect::<Vec<_>>(),
+                        )
+                        .spacing(1)
+                        .padding(10),
+                    ))
+                    .height(Length::Fill)
+                    .width(Length::Fill),
+                ]
+                .spacing(10)
+                .into()
+            }
+            Some(Err(e)) => text(format!("⚠️ {}", e)).size(14).into(),
+            None => text("Выберите оба файла").size(14).into(),
+        };
+
+        column![
+            text("Сравнение логов").size(24),
+            picker(0, "📂 Исходный лог"),
+            picker(1, "📂 Новый лог"),
+            result,
+        ]
+        .spacing(15)
+        .padding(20)
+        .into()
+    }
+}
@@ -1,4 +1,3 @@
-use serialport::SerialPort;
 use std::env;
 use std::fs::File;
 use std::io::{Read, Write};
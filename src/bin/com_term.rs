@@ -0,0 +1,5 @@
+#![windows_subsystem = "windows"]
+
+fn main() -> iced::Result {
+    com_terminal::run()
+}
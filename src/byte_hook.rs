@@ -0,0 +1,66 @@
+use rhai::{Blob, Dynamic, Engine, Scope, AST};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Hooks run on the UI thread, so a runaway script is cut off quickly
+const CALL_TIMEOUT: Duration = Duration::from_millis(50);
+
+pub struct ByteHook {
+    pub name: String,
+    engine: Engine,
+    ast: AST,
+    deadline: Arc<Mutex<Instant>>,
+}
+
+impl ByteHook {
+    pub fn load(name: String, source: &str) -> Result<Self, String> {
+        let deadline = Arc::new(Mutex::new(Instant::now()));
+        let mut engine = Engine::new();
+        // No imports, no console output, bounded memory and recursion
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        engine.set_max_call_levels(32);
+        engine.set_max_array_size(1 << 20);
+        engine.set_max_string_size(1 << 20);
+        let watched = Arc::clone(&deadline);
+        engine.on_progress(move |_| {
+            (Instant::now() > *watched.lock().unwrap()).then_some(Dynamic::UNIT)
+        });
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        let hook = Self {
+            name,
+            engine,
+            ast,
+            deadline,
+        };
+        if !hook.defines("on_send") && !hook.defines("on_recv") {
+            return Err("скрипт не определяет on_send или on_recv".to_string());
+        }
+        Ok(hook)
+    }
+
+    pub fn on_send(&self, bytes: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        self.call("on_send", bytes)
+    }
+
+    pub fn on_recv(&self, bytes: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        self.call("on_recv", bytes)
+    }
+
+    fn defines(&self, function: &str) -> bool {
+        self.ast.iter_functions().any(|f| f.name == function)
+    }
+
+    // None when the script leaves this direction alone
+    fn call(&self, function: &str, bytes: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        if !self.defines(function) {
+            return Ok(None);
+        }
+        *self.deadline.lock().unwrap() = Instant::now() + CALL_TIMEOUT;
+        self.engine
+            .call_fn::<Blob>(&mut Scope::new(), &self.ast, function, (bytes.to_vec(),))
+            .map(Some)
+            .map_err(|e| format!("{}: {}", function, e))
+    }
+}
@@ -0,0 +1,368 @@
+use crate::hex;
+use crate::line::*;
+use crate::serial::*;
+use iced::{Color, Theme};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Accent colors for each line kind, stored as "#RRGGBB" so the config stays readable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineColors {
+    pub rx: String,
+    pub tx: String,
+    pub info: String,
+    pub error: String,
+    // Added later, so older configs without it still load
+    #[serde(default = "default_alert_color")]
+    pub alert: String,
+}
+
+pub fn default_alert_color() -> String {
+    "#FFB020".into()
+}
+
+impl LineColors {
+    pub fn for_theme(theme: &Theme) -> Self {
+        if theme.extended_palette().is_dark {
+            Self {
+                rx: "#8FE388".into(),
+                tx: "#7EB6FF".into(),
+                info: "#C8C8C8".into(),
+                error: "#FF6B6B".into(),
+                alert: default_alert_color(),
+            }
+        } else {
+            Self {
+                rx: "#1E7B1E".into(),
+                tx: "#1F4FA8".into(),
+                info: "#505050".into(),
+                error: "#C0262D".into(),
+                alert: "#B35C00".into(),
+            }
+        }
+    }
+
+    pub fn get(&self, kind: LineKind) -> &str {
+        match kind {
+            LineKind::Rx => &self.rx,
+            LineKind::Tx => &self.tx,
+            LineKind::Info => &self.info,
+            LineKind::Error => &self.error,
+            LineKind::Alert => &self.alert,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: LineKind) -> &mut String {
+        match kind {
+            LineKind::Rx => &mut self.rx,
+            LineKind::Tx => &mut self.tx,
+            LineKind::Info => &mut self.info,
+            LineKind::Error => &mut self.error,
+            LineKind::Alert => &mut self.alert,
+        }
+    }
+
+    pub fn color(&self, kind: LineKind) -> Color {
+        parse_hex_color(self.get(kind)).unwrap_or(Color::WHITE)
+    }
+}
+
+impl Default for LineColors {
+    fn default() -> Self {
+        Self::for_theme(&Theme::Dark)
+    }
+}
+
+// Settings persisted between runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub line_colors: LineColors,
+    // Longer lines are cut in the view; the stored text stays complete
+    pub max_line_len: usize,
+    // Received text is shown broken every N chars; 0 disables
+    pub rx_wrap: usize,
+    // Brackets each send together with the responses that follow it
+    pub group_exchanges: bool,
+    // How long after a send received lines still count as its response; 0 = until the next send
+    pub exchange_window_ms: u64,
+    // Shell prompt echoed by the device, stripped or dimmed in the view; empty disables
+    pub prompt: String,
+    pub prompt_mode: PromptMode,
+    // Removes binary noise from the view; saved raw bytes are untouched
+    pub rx_filter: RxFilter,
+    pub rx_filter_range: (u8, u8),
+    pub nul_handling: NulHandling,
+    // Bytes per hexdump row, one of HEXDUMP_WIDTHS
+    pub hexdump_width: usize,
+    // Pulls the first number out of each received line for the status bar trend
+    pub value_sparkline: bool,
+    // Command framing with \r \n \t \xNN escapes, empty disables each part.
+    // Outgoing order: prefix, payload, suffix, checksum, line ending
+    pub send_prefix: String,
+    pub send_suffix: String,
+    pub send_checksum: SendChecksum,
+    // Minutes without RX/TX before the port is closed; 0 disables
+    pub idle_timeout_min: u64,
+    // Files above this many KB need confirmation before sending; 0 disables
+    pub large_file_kb: u64,
+    // Extra open attempts after a failed connect and the pause between them; 0 retries = one try
+    pub connect_retries: u32,
+    pub connect_retry_delay_ms: u64,
+    // Reopen the port when a send fails because the link dropped, using the retries above,
+    // and optionally send the failed data once more
+    pub reconnect_on_send_error: bool,
+    pub resend_after_reconnect: bool,
+    // Append new lines to the log file after this many received KB / seconds; 0 disables
+    pub autosave_kb: u64,
+    pub autosave_secs: u64,
+    // false: Enter breaks the line in a multi-line input and Ctrl+Enter sends
+    pub enter_sends: bool,
+    // Return focus to the command field after a send so the next command can be typed at once
+    pub keep_input_focus: bool,
+    // With timestamps shown, chunks that continue an unfinished line join it instead of
+    // starting a new stamped line
+    pub line_timestamps: bool,
+    // Prefix lines with the port they came from, in that port's color, so logs spanning
+    // several devices stay readable; ports without a chosen color get one from the palette
+    pub tag_ports: bool,
+    // Color received text by its ANSI SGR codes and hide other escape sequences
+    pub ansi_colors: bool,
+    // Terminal lines in a monospace font; off uses the proportional UI font
+    pub monospace_terminal: bool,
+    // Keep the terminal scrolled to new output while the view is at the bottom
+    pub follow_output: bool,
+    pub port_colors: BTreeMap<String, String>,
+    // How often the open port is polled for incoming data
+    pub poll_interval_ms: u64,
+    // Received data is split into entries at this byte; None keeps one entry per read
+    pub frame_delimiter: Option<u8>,
+    // Last successfully opened port, reconnected at launch when `auto_connect` is set
+    pub auto_connect: bool,
+    pub last_port: Option<String>,
+    pub last_baud: Option<u32>,
+    // Most recently opened first, one entry per port; shown as quick-connect chips
+    pub recent_ports: Vec<RecentPort>,
+    pub show_recent_ports: bool,
+    // USB VID:PID connected to at `last_baud` whenever it shows up in the port list
+    pub pinned_usb: Option<(u16, u16)>,
+    pub pinned_usb_enabled: bool,
+    pub profiles: Vec<ConnectionProfile>,
+    // Received text containing this raises an alert; empty disables
+    pub alert_pattern: String,
+    // System beep; on by default only where there is one to call (see system_beep)
+    pub alert_sound: bool,
+    pub alert_flash: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            line_colors: LineColors::default(),
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            rx_wrap: 0,
+            group_exchanges: false,
+            exchange_window_ms: 500,
+            prompt: String::new(),
+            prompt_mode: PromptMode::Strip,
+            rx_filter: RxFilter::Off,
+            rx_filter_range: (0x20, 0x7E),
+            nul_handling: NulHandling::Token,
+            hexdump_width: DEFAULT_HEXDUMP_WIDTH,
+            value_sparkline: false,
+            send_prefix: String::new(),
+            send_suffix: String::new(),
+            send_checksum: SendChecksum::None,
+            idle_timeout_min: 0,
+            large_file_kb: 1024,
+            connect_retries: 0,
+            connect_retry_delay_ms: 500,
+            reconnect_on_send_error: false,
+            resend_after_reconnect: false,
+            autosave_kb: 0,
+            autosave_secs: 0,
+            enter_sends: true,
+            keep_input_focus: true,
+            line_timestamps: false,
+            tag_ports: false,
+            ansi_colors: false,
+            monospace_terminal: true,
+            follow_output: true,
+            port_colors: BTreeMap::new(),
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            frame_delimiter: None,
+            auto_connect: false,
+            last_port: None,
+            last_baud: None,
+            recent_ports: Vec::new(),
+            show_recent_ports: true,
+            pinned_usb: None,
+            pinned_usb_enabled: false,
+            profiles: Vec::new(),
+            alert_pattern: String::new(),
+            alert_sound: cfg!(any(windows, target_os = "macos")),
+            alert_flash: true,
+        }
+    }
+}
+
+impl AppConfig {
+    // The user's config directory (%APPDATA%, ~/.config, ~/Library/Application Support),
+    // so installs in Program Files or /usr/bin can keep their settings
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .map(|dir| dir.join(CONFIG_DIR).join(CONFIG_FILE))
+            .unwrap_or_else(Self::exe_path)
+    }
+
+    // Where earlier versions kept it; also the fallback when the OS has no config directory
+    fn exe_path() -> PathBuf {
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(CONFIG_FILE)))
+            .unwrap_or_else(|| PathBuf::from(CONFIG_FILE))
+    }
+
+    // A missing or unreadable config silently falls back to defaults. A config left next
+    // to the executable is still picked up until the first save writes the new one
+    pub fn load() -> Self {
+        [Self::path(), Self::exe_path()]
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, content).map_err(|e| e.to_string())
+    }
+}
+
+pub const CONFIG_FILE: &str = "com_terminal.json";
+pub const CONFIG_DIR: &str = "com_terminal";
+pub const DEFAULT_MAX_LINE_LEN: usize = 2000;
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    pub port_name: String,
+    pub baud_rate: u32,
+    pub started: String,
+    pub duration: Duration,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl SessionRecord {
+    pub fn summary(&self) -> String {
+        format!(
+            "Сессия {} @{} длилась {}, RX {} / TX {} байт",
+            self.port_name,
+            self.baud_rate,
+            format_uptime(self.duration),
+            self.rx_bytes,
+            self.tx_bytes
+        )
+    }
+}
+
+pub const SESSION_HISTORY_LEN: usize = 20;
+
+pub const RECENT_PORTS_LEN: usize = 5;
+
+// Parameters a port was last opened with, enough to open it again in one click
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentPort {
+    pub port_name: String,
+    pub baud_rate: u32,
+    pub framing: String,
+}
+
+// Named set of connection parameters for switching between devices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionProfile {
+    pub name: String,
+    pub port_name: Option<String>,
+    pub baud_rate: u32,
+    pub framing: String,
+    pub exclusive: bool,
+    pub line_ending: LineEnding,
+    // The ';'-separated command list of the repeat sender
+    pub commands: String,
+}
+
+impl std::fmt::Display for ConnectionProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// Everything needed to reproduce what the user saw, written as one JSON file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub saved_at: chrono::DateTime<chrono::Local>,
+    pub port_name: Option<String>,
+    pub baud_rate: u32,
+    pub framing: String,
+    pub exclusive: bool,
+    pub connected: bool,
+    pub session_started: Option<String>,
+    pub session_duration_secs: Option<f64>,
+    pub sent_bytes: u64,
+    pub received_bytes: u64,
+    pub lines: Vec<SnapshotLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotLine {
+    pub kind: LineKind,
+    pub time: chrono::DateTime<chrono::Local>,
+    pub text: String,
+    pub repeats: u32,
+    // Payload as "AA BB" so the file stays readable
+    pub hex: String,
+    #[serde(default)]
+    pub port: Option<String>,
+}
+
+impl SnapshotLine {
+    pub fn from_line(line: &TerminalLine) -> Self {
+        Self {
+            kind: line.kind,
+            time: line.time,
+            text: line.text.clone(),
+            repeats: line.repeats,
+            hex: hex::bytes_to_hex(&line.bytes),
+            port: line.port.clone(),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut shown = format!("[{}] ", self.time.format("%H:%M:%S%.3f"));
+        if let Some(port) = &self.port {
+            shown.push_str(&format!("[{}] ", port_label(port)));
+        }
+        shown.push_str(&self.text);
+        if self.repeats > 1 {
+            shown.push_str(&format!(" (x{})", self.repeats));
+        }
+        shown
+    }
+}
+
+// Accepts "#RRGGBB" or "RRGGBB"
+pub fn parse_hex_color(value: &str) -> Result<Color, String> {
+    let digits = value.trim().trim_start_matches('#');
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' не в формате #RRGGBB", value));
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+    Ok(Color::from_rgb8(channel(0), channel(2), channel(4)))
+}
@@ -1,17 +1,75 @@
-use rfd::FileDialog;
+use crate::hex;
+use crate::traffic_chart;
 
-pub fn open_file_blocking() -> Result<String, String> {
-    if let Some(p) = FileDialog::new().pick_file() {
-        std::fs::read_to_string(p).map_err(|e| e.to_string())
-    } else {
-        Ok(String::new())
-    }
+// Blocking file picker returning the file name and its contents, None if cancelled
+pub fn pick_file_bytes() -> Result<Option<(String, Vec<u8>)>, String> {
+    let Some(path) = rfd::FileDialog::new().pick_file() else {
+        return Ok(None);
+    };
+    let data = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file.bin".to_string());
+    Ok(Some((name, data)))
+}
+
+pub fn save_session(json: String) -> Result<Option<String>, String> {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_file_name("com_session.json")
+        .save_file()
+    else {
+        return Ok(None);
+    };
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(Some(path.display().to_string()))
+}
+
+pub fn save_recording(json: String) -> Result<Option<String>, String> {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_file_name("tx_recording.json")
+        .save_file()
+    else {
+        return Ok(None);
+    };
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(Some(path.display().to_string()))
+}
+
+pub fn save_capture(data: Vec<u8>) -> Result<Option<String>, String> {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name("capture.bin")
+        .save_file()
+    else {
+        return Ok(None);
+    };
+    std::fs::write(&path, data).map_err(|e| e.to_string())?;
+    Ok(Some(path.display().to_string()))
+}
+
+pub fn save_chart(samples: Vec<(u64, u64)>) -> Result<Option<String>, String> {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("PNG", &["png"])
+        .add_filter("SVG", &["svg"])
+        .set_file_name("traffic.png")
+        .save_file()
+    else {
+        return Ok(None);
+    };
+    traffic_chart::save(&path, &samples)?;
+    Ok(Some(path.display().to_string()))
 }
 
-pub fn save_file_blocking(default_name: &str, content: &str) -> Result<(), String> {
-    if let Some(p) = FileDialog::new().set_file_name(default_name).save_file() {
-        std::fs::write(p, content).map_err(|e| e.to_string())
-    } else {
-        Ok(())
+// Hex text separated by whitespace or commas; errors name the 1-based line
+pub fn parse_hex_file(content: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.replace(',', " ");
+        let parsed =
+            hex::hex_to_bytes(&line).map_err(|e| format!("строка {}: {}", number + 1, e))?;
+        bytes.extend(parsed);
     }
+    Ok(bytes)
 }
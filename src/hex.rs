@@ -6,13 +6,25 @@ pub fn bytes_to_hex(bytes: &[u8]) -> String {
         .join(" ")
 }
 
+// Whitespace between digits is ignored; errors report the 1-based character position
 pub fn hex_to_bytes(s: &str) -> Result<Vec<u8>, String> {
-    let cleaned = s.split_whitespace().collect::<Vec<_>>().join("");
-    if cleaned.len() % 2 != 0 {
-        return Err("Odd length".into());
+    let mut bytes = Vec::new();
+    let mut high_nibble: Option<u8> = None;
+    for (pos, c) in s.chars().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let digit = c
+            .to_digit(16)
+            .ok_or_else(|| format!("недопустимый символ '{}' в позиции {}", c, pos + 1))?
+            as u8;
+        match high_nibble.take() {
+            Some(high) => bytes.push((high << 4) | digit),
+            None => high_nibble = Some(digit),
+        }
     }
-    (0..cleaned.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&cleaned[i..i + 2], 16).map_err(|e| e.to_string()))
-        .collect()
+    if high_nibble.is_some() {
+        return Err("нечетное количество HEX-цифр".into());
+    }
+    Ok(bytes)
 }
@@ -0,0 +1,35 @@
+// Serial terminal: the iced app lives in `app`, the rest is shared plumbing it builds on
+
+pub mod ansi;
+pub mod app;
+pub mod config;
+pub mod file;
+pub mod hex;
+pub mod line;
+
+// Reader and writer threads for an open port; the app only sees their events
+pub mod port_io;
+pub mod recording;
+pub mod serial;
+
+// Tiny automation language: one step per line, blank lines and '#' comments skipped
+//   SEND <text>
+//   EXPECT <substring> <timeout_ms>
+pub mod script;
+
+// Optional rhai hook that rewrites bytes on their way to and from the port.
+// The script defines `on_send(bytes)` and/or `on_recv(bytes)`, each taking and returning a blob
+#[cfg(feature = "scripting")]
+pub mod byte_hook;
+
+// Line diff via longest common subsequence; quadratic, so very large logs are refused
+pub mod line_diff;
+
+// Traffic chart drawn with plotters; the on-screen SVG and exported files share `draw`
+pub mod traffic_chart;
+
+// Sender side of the ZMODEM protocol: hex headers for control frames, CRC-32 binary
+// headers and data subpackets for the file itself
+pub mod zmodem;
+
+pub use app::run;
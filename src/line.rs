@@ -0,0 +1,642 @@
+use crate::hex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineKind {
+    Rx,
+    Tx,
+    Info,
+    Error,
+    Alert,
+}
+
+impl LineKind {
+    pub const ALL: [LineKind; 5] = [
+        LineKind::Rx,
+        LineKind::Tx,
+        LineKind::Info,
+        LineKind::Error,
+        LineKind::Alert,
+    ];
+}
+
+#[derive(Debug, Clone)]
+pub struct TerminalLine {
+    pub kind: LineKind,
+    pub text: String,
+    // How many identical consecutive receptions this line stands for
+    pub repeats: u32,
+    // Wall-clock time of the first occurrence
+    pub time: chrono::DateTime<chrono::Local>,
+    // Monotonic time since the previous line was pushed
+    pub delta: Duration,
+    // Payload behind RX/TX lines, empty for messages
+    pub bytes: Vec<u8>,
+    // Port that was connected when the line was added
+    pub port: Option<String>,
+    // Send this line belongs to: the TX line itself or an RX line inside its response window
+    pub exchange: Option<u64>,
+}
+
+impl TerminalLine {
+    // True for received lines where the device prompt starts one of the decoded lines
+    pub fn has_prompt(&self, prompt: &str) -> bool {
+        !prompt.is_empty()
+            && self.kind == LineKind::Rx
+            && self
+                .text
+                .strip_prefix("<- ")
+                .unwrap_or(&self.text)
+                .lines()
+                .any(|line| line.starts_with(prompt))
+    }
+
+    pub fn display(&self, max_len: usize) -> String {
+        let mut shown = truncate_line(&self.text, max_len);
+        if self.repeats > 1 {
+            shown.push_str(&format!(" (x{})", self.repeats));
+        }
+        shown
+    }
+
+    pub fn render(&self, options: &RenderOptions<'_>) -> String {
+        let mut prefix = String::new();
+        if options.show_time {
+            prefix.push_str(&format!("[{}] ", self.time.format("%H:%M:%S%.3f")));
+        }
+        if options.show_delta {
+            prefix.push_str(&format!("+{:.3}s ", self.delta.as_secs_f64()));
+        }
+        if self.kind == LineKind::Rx
+            && options.display_mode != DisplayMode::Text
+            && !self.bytes.is_empty()
+        {
+            let rows = options
+                .display_mode
+                .render(&self.bytes, options.hexdump_width);
+            let mut body = if rows.contains('\n') {
+                format!("<-\n{}", rows)
+            } else {
+                format!("<- {}", rows)
+            };
+            body = truncate_line(&body, options.max_len);
+            if self.repeats > 1 {
+                body.push_str(&format!(" (x{})", self.repeats));
+            }
+            return prefix + body.as_str();
+        }
+        let mut body = self.display(options.max_len);
+        if options.strip_prompt && self.kind == LineKind::Rx {
+            body = strip_prompt(&body, options.prompt);
+        }
+        if self.kind == LineKind::Rx && options.wrap_at > 0 {
+            body = wrap_every(&body, options.wrap_at);
+        }
+        if options.show_eol {
+            prefix + show_line_endings(&body).as_str()
+        } else {
+            prefix + body.as_str()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportDirection {
+    #[default]
+    Both,
+    Rx,
+    Tx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Text,
+    Hex,
+}
+
+// Line endings written to the log file; the live buffer keeps whatever the device sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaveLineEnding {
+    #[default]
+    AsReceived,
+    Lf,
+    CrLf,
+}
+
+impl SaveLineEnding {
+    pub fn apply(self, text: String) -> String {
+        if self == SaveLineEnding::AsReceived {
+            return text;
+        }
+        let unified = text.replace("\r\n", "\n").replace('\r', "\n");
+        match self {
+            SaveLineEnding::CrLf => unified.replace('\n', "\r\n"),
+            _ => unified,
+        }
+    }
+}
+
+impl TerminalLine {
+    // Log file form of the line, or None when the direction filter drops it
+    pub fn export(
+        &self,
+        direction: ExportDirection,
+        format: ExportFormat,
+        tag_port: bool,
+    ) -> Option<String> {
+        match (direction, self.kind) {
+            (ExportDirection::Both, _)
+            | (ExportDirection::Rx, LineKind::Rx)
+            | (ExportDirection::Tx, LineKind::Tx) => {}
+            _ => return None,
+        }
+        let time = match (&self.port, tag_port) {
+            (Some(port), true) => format!("{}] [{}", self.time.format("%H:%M:%S%.3f"), port),
+            _ => self.time.format("%H:%M:%S%.3f").to_string(),
+        };
+        Some(match format {
+            ExportFormat::Hex if !self.bytes.is_empty() => format!(
+                "[{}] {} {}",
+                time,
+                self.kind,
+                hex::bytes_to_hex(&self.bytes)
+            ),
+            _ => format!("[{}] {}", time, self.display(usize::MAX)),
+        })
+    }
+}
+
+// View-only switches; stored lines are never rewritten by them
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions<'a> {
+    pub show_time: bool,
+    pub show_delta: bool,
+    pub show_eol: bool,
+    pub max_len: usize,
+    // Break received text every N chars for display; 0 disables
+    pub wrap_at: usize,
+    pub prompt: &'a str,
+    pub strip_prompt: bool,
+    pub display_mode: DisplayMode,
+    pub hexdump_width: usize,
+}
+
+// How received bytes are shown; switching re-renders everything already received
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    #[default]
+    Text,
+    Hex,
+    Hexdump,
+    Escaped,
+    Unsigned,
+    Signed,
+}
+
+// Values per row in the decimal modes
+const DECIMAL_COLUMNS: usize = 16;
+
+// Bytes per hexdump row the settings offer
+pub const HEXDUMP_WIDTHS: [usize; 3] = [8, 16, 32];
+pub const DEFAULT_HEXDUMP_WIDTH: usize = 16;
+
+// Anything else, e.g. from a hand-edited config, falls back to the default
+pub fn hexdump_width(width: usize) -> usize {
+    if HEXDUMP_WIDTHS.contains(&width) {
+        width
+    } else {
+        DEFAULT_HEXDUMP_WIDTH
+    }
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 6] = [
+        DisplayMode::Text,
+        DisplayMode::Hex,
+        DisplayMode::Hexdump,
+        DisplayMode::Escaped,
+        DisplayMode::Unsigned,
+        DisplayMode::Signed,
+    ];
+
+    // Multi-row modes separate rows with '\n'; there is no trailing newline.
+    // `hexdump_width` is bytes per row in the hexdump
+    pub fn render(&self, bytes: &[u8], hexdump_width: usize) -> String {
+        match self {
+            DisplayMode::Text => String::from_utf8_lossy(bytes).into_owned(),
+            DisplayMode::Hex => hex::bytes_to_hex(bytes),
+            DisplayMode::Hexdump => bytes
+                .chunks(hexdump_width)
+                .enumerate()
+                .map(|(i, row)| {
+                    let ascii: String = row
+                        .iter()
+                        .map(|&b| {
+                            if b.is_ascii_graphic() || b == b' ' {
+                                b as char
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect();
+                    // Short last rows are padded so the ASCII gutter stays in its column
+                    format!(
+                        "{:08X}  {:<hex_width$}  |{}|",
+                        i * hexdump_width,
+                        hex::bytes_to_hex(row),
+                        ascii,
+                        hex_width = hexdump_width * 3 - 1
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            DisplayMode::Escaped => bytes
+                .iter()
+                .flat_map(|&b| std::ascii::escape_default(b))
+                .map(char::from)
+                .collect(),
+            DisplayMode::Unsigned => decimal_rows(bytes, |b| format!("{:>3}", b)),
+            DisplayMode::Signed => decimal_rows(bytes, |b| format!("{:>4}", b as i8)),
+        }
+    }
+}
+
+impl std::fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayMode::Text => write!(f, "Текст"),
+            DisplayMode::Hex => write!(f, "HEX"),
+            DisplayMode::Hexdump => write!(f, "Hexdump"),
+            DisplayMode::Escaped => write!(f, "Экранированный"),
+            DisplayMode::Unsigned => write!(f, "Dec 0..255"),
+            DisplayMode::Signed => write!(f, "Dec -128..127"),
+        }
+    }
+}
+
+// Right-aligned values, DECIMAL_COLUMNS per row so samples line up
+fn decimal_rows(bytes: &[u8], format: impl Fn(u8) -> String) -> String {
+    bytes
+        .chunks(DECIMAL_COLUMNS)
+        .map(|row| {
+            let values: Vec<String> = row.iter().map(|&b| format(b)).collect();
+            values.join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Removes the prompt from the start of every decoded line of an "<- ..." entry
+pub fn strip_prompt(body: &str, prompt: &str) -> String {
+    if prompt.is_empty() {
+        return body.to_string();
+    }
+    let (head, data) = match body.strip_prefix("<- ") {
+        Some(data) => ("<- ", data),
+        None => ("", body),
+    };
+    let stripped: String = data
+        .split_inclusive('\n')
+        .map(|line| line.strip_prefix(prompt).unwrap_or(line))
+        .collect();
+    format!("{}{}", head, stripped)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PromptMode {
+    #[default]
+    Strip,
+    Dim,
+}
+
+// What happens to received bytes outside the allowed range before decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RxFilter {
+    #[default]
+    Off,
+    Drop,
+    Replace,
+}
+
+// What decoded text does with NUL padding; raw bytes keep it either way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NulHandling {
+    Strip,
+    #[default]
+    Token,
+    Keep,
+}
+
+impl NulHandling {
+    pub fn apply(self, text: String) -> String {
+        if !text.contains('\0') {
+            return text;
+        }
+        match self {
+            NulHandling::Strip => text.replace('\0', ""),
+            NulHandling::Token => text.replace('\0', "<NUL>"),
+            NulHandling::Keep => text,
+        }
+    }
+}
+
+// Keeps bytes in `range` plus tab, CR and LF; the rest are dropped or shown as '.'
+pub fn filter_bytes(bytes: &[u8], filter: RxFilter, range: (u8, u8)) -> Cow<'_, [u8]> {
+    let allowed = |b: u8| (range.0..=range.1).contains(&b) || matches!(b, b'\t' | b'\r' | b'\n');
+    match filter {
+        RxFilter::Off => Cow::Borrowed(bytes),
+        RxFilter::Drop => Cow::Owned(bytes.iter().copied().filter(|&b| allowed(b)).collect()),
+        RxFilter::Replace => Cow::Owned(
+            bytes
+                .iter()
+                .map(|&b| if allowed(b) { b } else { b'.' })
+                .collect(),
+        ),
+    }
+}
+
+// First decimal number in a line, e.g. 21.5 in "temp=21.5C"
+pub fn first_number(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+    let start = (0..bytes.len()).find(|&i| {
+        bytes[i].is_ascii_digit()
+            || (bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+    })?;
+    let mut end = start + 1;
+    let mut seen_dot = false;
+    while let Some(&b) = bytes.get(end) {
+        if b == b'.' && !seen_dot && bytes.get(end + 1).is_some_and(u8::is_ascii_digit) {
+            seen_dot = true;
+        } else if !b.is_ascii_digit() {
+            break;
+        }
+        end += 1;
+    }
+    text[start..end].parse().ok()
+}
+
+// Inserts a line break after every `width` chars; real line breaks restart the count
+pub fn wrap_every(text: &str, width: usize) -> String {
+    let mut wrapped = String::with_capacity(text.len() + text.len() / width);
+    let mut column = 0;
+    for c in text.chars() {
+        if c == '\n' {
+            column = 0;
+        } else if column == width {
+            wrapped.push('\n');
+            column = 1;
+        } else {
+            column += 1;
+        }
+        wrapped.push(c);
+    }
+    wrapped
+}
+
+// Makes CR/LF visible as ␍/␊ while keeping the line break after LF
+pub fn show_line_endings(text: &str) -> String {
+    text.replace('\r', "␍").replace('\n', "␊\n")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LineEnding {
+    #[default]
+    None,
+    Lf,
+    Cr,
+    CrLf,
+}
+
+impl LineEnding {
+    pub const ALL: [LineEnding; 4] = [
+        LineEnding::None,
+        LineEnding::Lf,
+        LineEnding::Cr,
+        LineEnding::CrLf,
+    ];
+
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::None => b"",
+            LineEnding::Lf => b"\n",
+            LineEnding::Cr => b"\r",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+// Appended after the suffix and covers prefix, payload and suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SendChecksum {
+    #[default]
+    None,
+    Sum8,
+    Xor8,
+    // Modbus RTU CRC, low byte first
+    Crc16Modbus,
+}
+
+impl SendChecksum {
+    pub const ALL: [SendChecksum; 4] = [
+        SendChecksum::None,
+        SendChecksum::Sum8,
+        SendChecksum::Xor8,
+        SendChecksum::Crc16Modbus,
+    ];
+
+    pub fn append(self, bytes: &mut Vec<u8>) {
+        match self {
+            SendChecksum::None => {}
+            SendChecksum::Sum8 => bytes.push(bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b))),
+            SendChecksum::Xor8 => bytes.push(bytes.iter().fold(0u8, |acc, &b| acc ^ b)),
+            SendChecksum::Crc16Modbus => {
+                let mut crc: u16 = 0xFFFF;
+                for &b in bytes.iter() {
+                    crc ^= u16::from(b);
+                    for _ in 0..8 {
+                        crc = if crc & 1 != 0 {
+                            (crc >> 1) ^ 0xA001
+                        } else {
+                            crc >> 1
+                        };
+                    }
+                }
+                bytes.extend_from_slice(&crc.to_le_bytes());
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SendChecksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendChecksum::None => write!(f, "Без суммы"),
+            SendChecksum::Sum8 => write!(f, "SUM8"),
+            SendChecksum::Xor8 => write!(f, "XOR8"),
+            SendChecksum::Crc16Modbus => write!(f, "CRC16 Modbus"),
+        }
+    }
+}
+
+impl std::fmt::Display for LineKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineKind::Rx => write!(f, "RX"),
+            LineKind::Tx => write!(f, "TX"),
+            LineKind::Info => write!(f, "Инфо"),
+            LineKind::Error => write!(f, "Ошибки"),
+            LineKind::Alert => write!(f, "Тревога"),
+        }
+    }
+}
+
+impl std::fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineEnding::None => write!(f, "Без окончания"),
+            LineEnding::Lf => write!(f, "LF (\\n)"),
+            LineEnding::Cr => write!(f, "CR (\\r)"),
+            LineEnding::CrLf => write!(f, "CR+LF"),
+        }
+    }
+}
+
+// Resolves bare '\r' (not part of "\r\n") in a received chunk.
+// Returns the text after the last bare CR, whether it should replace the current
+// RX line, and whether the chunk ended on a CR whose meaning depends on the next one.
+// Every chunk is shown as its own line, so "current line" means the last RX line;
+// a newline splitter must apply this before splitting so only the partial line is rewound.
+pub fn apply_carriage_returns(data: &str, cr_pending: bool) -> (String, bool, bool) {
+    let mut overwrite = cr_pending && !data.starts_with('\n');
+    let pending = data.ends_with('\r');
+    let body = data.strip_suffix('\r').unwrap_or(data);
+    let mut tail_start = 0;
+    for (i, _) in body.match_indices('\r') {
+        if !body[i + 1..].starts_with('\n') {
+            tail_start = i + 1;
+            overwrite = true;
+        }
+    }
+    (body[tail_start..].to_string(), overwrite, pending)
+}
+
+// Cuts on a char boundary and reports how many bytes were hidden
+pub fn truncate_line(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+    let mut cut = max_len;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    format!("{}…(+{} bytes)", &text[..cut], text.len() - cut)
+}
+
+// Cuts received bytes after every '\n'. Each piece says whether it starts a new line, given
+// whether the previous chunk left one open; also returns whether a line is open afterwards
+pub fn split_line_pieces(bytes: &[u8], mid_line: bool) -> (Vec<(bool, &[u8])>, bool) {
+    let mut starts_line = !mid_line;
+    let mut pieces = Vec::new();
+    for piece in bytes.split_inclusive(|&b| b == b'\n') {
+        pieces.push((starts_line, piece));
+        starts_line = true;
+    }
+    let mid_line = match bytes.last() {
+        Some(&last) => last != b'\n',
+        None => mid_line,
+    };
+    (pieces, mid_line)
+}
+
+// Formats a duration as HH:MM:SS, prefixing days for sessions longer than 24h
+pub fn format_uptime(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let days = secs / 86_400;
+    let hours = (secs % 86_400) / 3600;
+    let minutes = (secs % 3600) / 60;
+    let seconds = secs % 60;
+    if days > 0 {
+        format!("{}d {:02}:{:02}:{:02}", days, hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_cover_the_whole_frame() {
+        let frame = |checksum: SendChecksum| {
+            let mut bytes = b"123456789".to_vec();
+            checksum.append(&mut bytes);
+            bytes[9..].to_vec()
+        };
+        assert_eq!(frame(SendChecksum::None), b"");
+        assert_eq!(frame(SendChecksum::Sum8), [0xDD]);
+        assert_eq!(frame(SendChecksum::Xor8), [0x31]);
+        assert_eq!(frame(SendChecksum::Crc16Modbus), [0x37, 0x4B]);
+    }
+
+    #[test]
+    fn hexdump_rows_follow_the_width() {
+        let bytes: Vec<u8> = (0x41..0x41 + 20).collect();
+        let rows = DisplayMode::Hexdump.render(&bytes, 8);
+        let rows: Vec<&str> = rows.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], "00000008  49 4A 4B 4C 4D 4E 4F 50  |IJKLMNOP|");
+        assert_eq!(rows[2], "00000010  51 52 53 54              |QRST|");
+        assert_eq!(DisplayMode::Hexdump.render(&bytes, 32).lines().count(), 1);
+        assert_eq!(hexdump_width(12), DEFAULT_HEXDUMP_WIDTH);
+    }
+
+    #[test]
+    fn line_split_over_three_chunks_starts_once() {
+        let mut mid_line = false;
+        let mut starts = 0;
+        for chunk in [&b"tempera"[..], b"ture: 2", b"1.5 C\r\n"] {
+            let (pieces, open) = split_line_pieces(chunk, mid_line);
+            starts += pieces
+                .iter()
+                .filter(|(starts_line, _)| *starts_line)
+                .count();
+            mid_line = open;
+        }
+        assert_eq!(starts, 1);
+        assert!(!mid_line);
+    }
+
+    #[test]
+    fn first_number_skips_labels_and_units() {
+        assert_eq!(first_number("temp=21.5C"), Some(21.5));
+        assert_eq!(first_number("<- dT -3 K"), Some(-3.0));
+        assert_eq!(first_number("v1.2.3"), Some(1.2));
+        assert_eq!(first_number("OK."), None);
+    }
+
+    #[test]
+    fn nul_padding_is_stripped_or_marked() {
+        let padded = "OK\0\0\r\n".to_string();
+        assert_eq!(NulHandling::Strip.apply(padded.clone()), "OK\r\n");
+        assert_eq!(NulHandling::Token.apply(padded.clone()), "OK<NUL><NUL>\r\n");
+        assert_eq!(NulHandling::Keep.apply(padded.clone()), padded);
+    }
+
+    #[test]
+    fn line_split_stamps_each_line_in_a_chunk() {
+        let (pieces, open) = split_line_pieces(b"tail\nnext\npart", true);
+        assert_eq!(
+            pieces,
+            vec![
+                (false, &b"tail\n"[..]),
+                (true, &b"next\n"[..]),
+                (true, &b"part"[..]),
+            ]
+        );
+        assert!(open);
+    }
+}
@@ -0,0 +1,148 @@
+// Table cells are u32, so this caps the table at about 100 MB
+const MAX_CELLS: usize = 25_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+pub fn diff(old: &[String], new: &[String]) -> Result<Vec<DiffLine>, String> {
+    // Lines shared at both ends never need the table
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    let (n, m) = (a.len(), b.len());
+    if (n + 1).saturating_mul(m + 1) > MAX_CELLS {
+        return Err(format!(
+            "слишком много различающихся строк для сравнения ({} × {})",
+            n, m
+        ));
+    }
+
+    // lcs[i][j]: common subsequence length of a[i..] and b[j..]
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if a[i] == b[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut out: Vec<DiffLine> = old[..prefix].iter().cloned().map(DiffLine::Same).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            out.push(DiffLine::Same(a[i].clone()));
+            i += 1;
+            j += 1;
+        } else if lcs[at(i + 1, j)] >= lcs[at(i, j + 1)] {
+            out.push(DiffLine::Removed(a[i].clone()));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(b[j].clone()));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().cloned().map(DiffLine::Removed));
+    out.extend(b[j..].iter().cloned().map(DiffLine::Added));
+    out.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .cloned()
+            .map(DiffLine::Same),
+    );
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn identical_inputs_are_all_same() {
+        let text = lines("a b c");
+        assert_eq!(
+            diff(&text, &text).unwrap(),
+            text.into_iter().map(DiffLine::Same).collect::<Vec<_>>()
+        );
+        assert_eq!(diff(&[], &[]).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn pure_additions_and_removals() {
+        assert_eq!(
+            diff(&lines("a c"), &lines("a b c d")).unwrap(),
+            vec![
+                DiffLine::Same("a".into()),
+                DiffLine::Added("b".into()),
+                DiffLine::Same("c".into()),
+                DiffLine::Added("d".into()),
+            ]
+        );
+        assert_eq!(
+            diff(&lines("x a b"), &lines("a")).unwrap(),
+            vec![
+                DiffLine::Removed("x".into()),
+                DiffLine::Same("a".into()),
+                DiffLine::Removed("b".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn change_in_the_middle_keeps_prefix_and_suffix() {
+        // Within a replaced run the old lines come before the new ones
+        assert_eq!(
+            diff(&lines("h1 h2 x y t"), &lines("h1 h2 z y w t")).unwrap(),
+            vec![
+                DiffLine::Same("h1".into()),
+                DiffLine::Same("h2".into()),
+                DiffLine::Removed("x".into()),
+                DiffLine::Added("z".into()),
+                DiffLine::Same("y".into()),
+                DiffLine::Added("w".into()),
+                DiffLine::Same("t".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_line_is_not_counted_in_both_prefix_and_suffix() {
+        assert_eq!(
+            diff(&lines("a b a"), &lines("a")).unwrap(),
+            vec![
+                DiffLine::Same("a".into()),
+                DiffLine::Removed("b".into()),
+                DiffLine::Removed("a".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn too_many_differing_lines_are_refused() {
+        // A shared prefix and suffix do not count against the limit
+        let shared: Vec<String> = (0..10_000).map(|i| format!("s{}", i)).collect();
+        let old: Vec<String> = (0..5_000).map(|i| format!("a{}", i)).collect();
+        let new: Vec<String> = (0..5_000).map(|i| format!("b{}", i)).collect();
+        let wrap = |middle: &[String]| [&shared[..], middle, &shared[..]].concat();
+        assert!(diff(&wrap(&old), &wrap(&new[..4_000])).is_ok());
+        let error = diff(&wrap(&old), &wrap(&new)).unwrap_err();
+        assert!(error.contains("5000 × 5000"), "{}", error);
+    }
+}
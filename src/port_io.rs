@@ -0,0 +1,604 @@
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use serialport::SerialPort;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::serial::{
+    is_connection_lost_error, is_transient_read_error, LineErrorDecoder, LineErrorSwitch,
+    WOULD_BLOCK_BACKOFF,
+};
+
+// A batch is handed over early once it grows this large, so a flooding device can't pile up
+const MAX_BATCH: usize = 64 * 1024;
+// Writes go out in slices this long so a stop can cut a long one short
+const WRITE_SLICE: usize = 1024;
+// How often a reader that is paused or locked out looks again
+const IDLE_BACKOFF: Duration = Duration::from_millis(10);
+// Pause after a read error that left the port usable, so it can't flood the log
+const READ_ERROR_BACKOFF: Duration = Duration::from_millis(200);
+
+// How the app reports a write once the writer thread is done with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteKind {
+    // Keystrokes and pings: only failures are logged
+    Quiet,
+    // Confirmed in the log once on the wire
+    Confirmed,
+    // The data a lost link kept from going out; a second failure ends the recovery
+    Resend,
+}
+
+#[derive(Debug, Clone)]
+pub struct WriteJob {
+    pub data: Vec<u8>,
+    pub kind: WriteKind,
+    // Jobs queued before the last discard_pending are dropped instead of written
+    epoch: u64,
+}
+
+impl WriteJob {
+    pub fn new(data: Vec<u8>, kind: WriteKind) -> Self {
+        Self {
+            data,
+            kind,
+            epoch: 0,
+        }
+    }
+}
+
+// Lets the reader thread take a new read timeout between reads, while it holds the handle
+pub trait ReadTimeout {
+    fn apply_read_timeout(&mut self, timeout: Duration);
+}
+
+impl ReadTimeout for Box<dyn SerialPort> {
+    fn apply_read_timeout(&mut self, timeout: Duration) {
+        let _ = self.set_timeout(timeout);
+    }
+}
+
+// What the port threads report back; errors are strings since io::Error is not Clone
+#[derive(Debug, Clone)]
+pub enum PortEvent {
+    // Bytes read within one batch window, with the time the last of them came in
+    Received(Vec<u8>, Instant),
+    // `lost` means the handle is dead and the reader thread has ended
+    ReadFailed {
+        error: String,
+        lost: bool,
+    },
+    // Bytes that came in with a framing or parity error; they are also in Received
+    LineErrors(usize),
+    Written(WriteJob),
+    WriteFailed {
+        job: WriteJob,
+        error: String,
+        lost: bool,
+    },
+    // Dropped unwritten by discard_pending or by the port closing
+    Discarded(WriteJob),
+}
+
+// An open port driven by two threads: one reads and hands the bytes over as events, one
+// drains a queue of writes. The UI thread only ever waits on the control handle, and only
+// for line toggles and status queries.
+pub struct PortIo<P> {
+    // Lent out whole to file transfers while reading is paused
+    port: Arc<Mutex<P>>,
+    control: Arc<Mutex<P>>,
+    jobs: Option<std::sync::mpsc::Sender<WriteJob>>,
+    epoch: Arc<AtomicU64>,
+    batch_ms: Arc<AtomicU64>,
+    // Read timeout the reader thread should switch to; 0 keeps the one the port opened with
+    timeout_ms: Arc<AtomicU64>,
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    // Set when the reader handle marks bad bytes; switched off while a transfer reads
+    line_errors: Option<LineErrorSwitch>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<P: Read + Write + ReadTimeout + Send + 'static> PortIo<P> {
+    pub fn new(reader: P, writer: P, control: P) -> (Self, UnboundedReceiver<PortEvent>) {
+        Self::with_line_errors(reader, writer, control, None)
+    }
+
+    // The reader decodes error marks whenever the port has a switch for them
+    fn with_line_errors(
+        reader: P,
+        writer: P,
+        control: P,
+        line_errors: Option<LineErrorSwitch>,
+    ) -> (Self, UnboundedReceiver<PortEvent>) {
+        let (events, receiver) = mpsc::unbounded();
+        let (jobs, queue) = std::sync::mpsc::channel();
+        let mut io = Self {
+            port: Arc::new(Mutex::new(reader)),
+            control: Arc::new(Mutex::new(control)),
+            jobs: Some(jobs),
+            epoch: Arc::new(AtomicU64::new(0)),
+            batch_ms: Arc::new(AtomicU64::new(0)),
+            timeout_ms: Arc::new(AtomicU64::new(0)),
+            paused: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+            line_errors,
+            threads: Vec::new(),
+        };
+        let reader = Reader {
+            port: Arc::clone(&io.port),
+            batch_ms: Arc::clone(&io.batch_ms),
+            timeout_ms: Arc::clone(&io.timeout_ms),
+            paused: Arc::clone(&io.paused),
+            stop: Arc::clone(&io.stop),
+            decoder: io.line_errors.as_ref().map(|_| LineErrorDecoder::default()),
+            events: events.clone(),
+        };
+        let epoch = Arc::clone(&io.epoch);
+        let stop = Arc::clone(&io.stop);
+        io.threads.push(std::thread::spawn(move || reader.run()));
+        io.threads.push(std::thread::spawn(move || {
+            write_loop(writer, queue, &epoch, &stop, &events)
+        }));
+        (io, receiver)
+    }
+}
+
+impl<P> PortIo<P> {
+    // Queues a write; false once the writer thread is gone
+    pub fn send(&self, mut job: WriteJob) -> bool {
+        job.epoch = self.epoch.load(Ordering::Relaxed);
+        self.jobs
+            .as_ref()
+            .is_some_and(|jobs| jobs.send(job).is_ok())
+    }
+
+    // Whatever is still queued comes back as Discarded instead of going out
+    pub fn discard_pending(&self) {
+        self.epoch.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Received bytes are collected this long before they are handed over as one chunk
+    pub fn set_batch_window(&self, window: Duration) {
+        self.batch_ms
+            .store(window.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn control(&self) -> MutexGuard<'_, P> {
+        self.control.lock().unwrap()
+    }
+
+    // For status queries from the poll tick, which should skip rather than wait
+    pub fn try_control(&self) -> Option<MutexGuard<'_, P>> {
+        self.control.try_lock().ok()
+    }
+
+    // Control handle for a task that outlives the borrow, like the reset pulse
+    pub fn shared_control(&self) -> Arc<Mutex<P>> {
+        Arc::clone(&self.control)
+    }
+
+    // Pauses the reader thread so a transfer gets every byte the device answers with
+    pub fn exclusive(&self) -> Exclusive<P> {
+        self.paused.store(true, Ordering::Relaxed);
+        Exclusive {
+            port: Arc::clone(&self.port),
+            paused: Arc::clone(&self.paused),
+            line_errors: self.line_errors.clone(),
+        }
+    }
+
+    // Whether LineErrors events can come at all; without marks bad bytes go unnoticed
+    pub fn reports_line_errors(&self) -> bool {
+        self.line_errors.is_some()
+    }
+}
+
+impl PortIo<Box<dyn SerialPort>> {
+    // `line_errors` must belong to `port`, which stays open as the reader handle
+    pub fn open(
+        port: Box<dyn SerialPort>,
+        line_errors: Option<LineErrorSwitch>,
+    ) -> serialport::Result<(Self, UnboundedReceiver<PortEvent>)> {
+        let writer = port.try_clone()?;
+        let control = port.try_clone()?;
+        Ok(Self::with_line_errors(port, writer, control, line_errors))
+    }
+
+    // The reader and control handles keep their own timeouts; the writer only ever
+    // writes, so its timeout doesn't matter. The reader handle may be lent to a transfer,
+    // so the reader thread picks the new value up before its next read instead
+    pub fn set_timeout(&self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout_ms
+            .store((timeout.as_millis() as u64).max(1), Ordering::Relaxed);
+        self.control().set_timeout(timeout)
+    }
+}
+
+// Joins both threads, so the device is really closed before it is opened again
+impl<P> Drop for PortIo<P> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.jobs = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+// The port lent to a file transfer; reading resumes once it is dropped
+pub struct Exclusive<P> {
+    port: Arc<Mutex<P>>,
+    paused: Arc<AtomicBool>,
+    line_errors: Option<LineErrorSwitch>,
+}
+
+impl<P> Exclusive<P> {
+    // Waits out a read already in progress, so no reply is lost to the reader thread.
+    // Error marks are off from here on: transfers want the bytes exactly as sent
+    pub fn lock(&self) -> MutexGuard<'_, P> {
+        let port = self.port.lock().unwrap();
+        if let Some(switch) = &self.line_errors {
+            switch(false);
+        }
+        port
+    }
+}
+
+impl<P> Drop for Exclusive<P> {
+    fn drop(&mut self) {
+        if let Some(switch) = &self.line_errors {
+            switch(true);
+        }
+        self.paused.store(false, Ordering::Relaxed);
+    }
+}
+
+struct Reader<P> {
+    port: Arc<Mutex<P>>,
+    batch_ms: Arc<AtomicU64>,
+    timeout_ms: Arc<AtomicU64>,
+    paused: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    decoder: Option<LineErrorDecoder>,
+    events: UnboundedSender<PortEvent>,
+}
+
+impl<P: Read + ReadTimeout> Reader<P> {
+    // The read timeout bounds how late a batch is handed over and how long a stop waits
+    fn run(mut self) {
+        let mut buffer = [0; 1024];
+        let mut pending = Vec::new();
+        let mut batch: Option<(Instant, Instant)> = None;
+        let mut timeout_ms = 0;
+        while !self.stop.load(Ordering::Relaxed) {
+            if let Some((first, last)) = batch {
+                let window = Duration::from_millis(self.batch_ms.load(Ordering::Relaxed));
+                if first.elapsed() >= window || pending.len() >= MAX_BATCH {
+                    let _ = self
+                        .events
+                        .unbounded_send(PortEvent::Received(std::mem::take(&mut pending), last));
+                    batch = None;
+                }
+            }
+            if self.paused.load(Ordering::Relaxed) {
+                std::thread::sleep(IDLE_BACKOFF);
+                continue;
+            }
+            // Locked out only by a transfer that has not set the pause flag back yet
+            let result = match self.port.try_lock() {
+                Ok(mut port) => {
+                    let wanted = self.timeout_ms.load(Ordering::Relaxed);
+                    if wanted != timeout_ms {
+                        port.apply_read_timeout(Duration::from_millis(wanted));
+                        timeout_ms = wanted;
+                    }
+                    port.read(&mut buffer)
+                }
+                Err(TryLockError::WouldBlock) => {
+                    std::thread::sleep(IDLE_BACKOFF);
+                    continue;
+                }
+                Err(TryLockError::Poisoned(_)) => return,
+            };
+            match result {
+                Ok(n) => {
+                    let before = pending.len();
+                    match &mut self.decoder {
+                        Some(decoder) => {
+                            let errors = decoder.decode(&buffer[..n], &mut pending);
+                            if errors > 0 {
+                                let _ = self.events.unbounded_send(PortEvent::LineErrors(errors));
+                            }
+                        }
+                        None => pending.extend_from_slice(&buffer[..n]),
+                    }
+                    // A read can end inside an error mark and leave nothing to show yet
+                    if pending.len() > before {
+                        let now = Instant::now();
+                        batch = Some((batch.map_or(now, |(first, _)| first), now));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(WOULD_BLOCK_BACKOFF)
+                }
+                Err(e) if is_transient_read_error(&e) => {}
+                Err(e) => {
+                    if let Some((_, last)) = batch.take() {
+                        let _ = self.events.unbounded_send(PortEvent::Received(
+                            std::mem::take(&mut pending),
+                            last,
+                        ));
+                    }
+                    let lost = is_connection_lost_error(&e);
+                    let _ = self.events.unbounded_send(PortEvent::ReadFailed {
+                        error: e.to_string(),
+                        lost,
+                    });
+                    if lost {
+                        return;
+                    }
+                    std::thread::sleep(READ_ERROR_BACKOFF);
+                }
+            }
+        }
+    }
+}
+
+// Writes leave in the order they were queued. Ends when the queue's sender is dropped
+fn write_loop<W: Write>(
+    mut port: W,
+    queue: std::sync::mpsc::Receiver<WriteJob>,
+    epoch: &AtomicU64,
+    stop: &AtomicBool,
+    events: &UnboundedSender<PortEvent>,
+) {
+    let stale =
+        |job: &WriteJob| stop.load(Ordering::Relaxed) || job.epoch != epoch.load(Ordering::Relaxed);
+    for job in queue {
+        let mut result = Ok(());
+        for slice in job.data.chunks(WRITE_SLICE) {
+            if stale(&job) {
+                break;
+            }
+            result = port.write_all(slice);
+            if result.is_err() {
+                break;
+            }
+        }
+        let event = match result {
+            Err(e) => PortEvent::WriteFailed {
+                lost: is_connection_lost_error(&e),
+                error: e.to_string(),
+                job,
+            },
+            Ok(()) if stale(&job) => PortEvent::Discarded(job),
+            Ok(()) => PortEvent::Written(job),
+        };
+        let _ = events.unbounded_send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    // Every read blocks like a port waiting out its timeout, after handing out `incoming`
+    // once; the first blocking read reports when it starts and when it returns
+    struct SlowLink {
+        incoming: Vec<u8>,
+        reading: Option<std::sync::mpsc::Sender<()>>,
+        read_done: Option<std::sync::mpsc::Sender<()>>,
+        written: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl SlowLink {
+        fn new(incoming: &[u8], written: &Arc<Mutex<Vec<u8>>>) -> Self {
+            Self {
+                incoming: incoming.to_vec(),
+                reading: None,
+                read_done: None,
+                written: Arc::clone(written),
+            }
+        }
+    }
+
+    impl Read for SlowLink {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if !self.incoming.is_empty() {
+                let n = self.incoming.len().min(buf.len());
+                buf[..n].copy_from_slice(&self.incoming[..n]);
+                self.incoming.drain(..n);
+                return Ok(n);
+            }
+            if let Some(reading) = self.reading.take() {
+                let _ = reading.send(());
+            }
+            std::thread::sleep(Duration::from_millis(500));
+            if let Some(read_done) = self.read_done.take() {
+                let _ = read_done.send(());
+            }
+            Err(io::ErrorKind::TimedOut.into())
+        }
+    }
+
+    impl ReadTimeout for SlowLink {
+        fn apply_read_timeout(&mut self, _: Duration) {}
+    }
+
+    impl Write for SlowLink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_goes_out_while_the_reader_is_blocked() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (reading, started) = std::sync::mpsc::channel();
+        let (read_done, finished) = std::sync::mpsc::channel();
+        let mut reader = SlowLink::new(b"", &written);
+        reader.reading = Some(reading);
+        reader.read_done = Some(read_done);
+        let (io, mut events) = PortIo::new(
+            reader,
+            SlowLink::new(b"", &written),
+            SlowLink::new(b"", &written),
+        );
+        started.recv().unwrap();
+        assert!(io.send(WriteJob::new(b"AT\r".to_vec(), WriteKind::Confirmed)));
+        let event = futures::executor::block_on(events.next()).unwrap();
+        // Written while the reader was still inside its blocking read
+        assert!(finished.try_recv().is_err());
+        match event {
+            PortEvent::Written(job) => assert_eq!(job.kind, WriteKind::Confirmed),
+            other => panic!("expected Written, got {:?}", other),
+        }
+        assert_eq!(*written.lock().unwrap(), b"AT\r");
+    }
+
+    #[test]
+    fn received_bytes_arrive_as_events() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (_io, mut events) = PortIo::new(
+            SlowLink::new(b"OK\r\n", &written),
+            SlowLink::new(b"", &written),
+            SlowLink::new(b"", &written),
+        );
+        match futures::executor::block_on(events.next()).unwrap() {
+            PortEvent::Received(data, _) => assert_eq!(data, b"OK\r\n"),
+            other => panic!("expected Received, got {:?}", other),
+        }
+    }
+
+    // Plays back scripted read results, then idles like a port with nothing to say
+    struct ScriptedLink(Vec<io::Result<Vec<u8>>>);
+
+    impl Read for ScriptedLink {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                std::thread::sleep(Duration::from_millis(10));
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            let data = self.0.remove(0)?;
+            buf[..data.len()].copy_from_slice(&data);
+            Ok(data.len())
+        }
+    }
+
+    impl ReadTimeout for ScriptedLink {
+        fn apply_read_timeout(&mut self, _: Duration) {}
+    }
+
+    impl Write for ScriptedLink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn serialport_eio_ends_the_read_loop() {
+        // What serialport makes of EIO when a USB adapter is pulled: no OS code left
+        let (io, mut events) = PortIo::new(
+            ScriptedLink(vec![
+                Err(io::Error::other("I/O error")),
+                Ok(b"late".to_vec()),
+            ]),
+            ScriptedLink(Vec::new()),
+            ScriptedLink(Vec::new()),
+        );
+        assert!(matches!(
+            futures::executor::block_on(events.next()).unwrap(),
+            PortEvent::ReadFailed { lost: true, .. }
+        ));
+        drop(io);
+        let rest: Vec<_> = futures::executor::block_on(events.collect());
+        assert!(
+            rest.is_empty(),
+            "reader went on after a lost link: {:?}",
+            rest
+        );
+    }
+
+    #[test]
+    fn other_bare_errors_leave_the_port_open() {
+        let (_io, mut events) = PortIo::new(
+            ScriptedLink(vec![
+                Err(io::Error::other("flush failed")),
+                Ok(b"ok".to_vec()),
+            ]),
+            ScriptedLink(Vec::new()),
+            ScriptedLink(Vec::new()),
+        );
+        assert!(matches!(
+            futures::executor::block_on(events.next()).unwrap(),
+            PortEvent::ReadFailed { lost: false, .. }
+        ));
+        assert!(matches!(
+            futures::executor::block_on(events.next()).unwrap(),
+            PortEvent::Received(data, _) if data == b"ok"
+        ));
+    }
+
+    #[test]
+    fn marked_bytes_are_counted_and_transfers_read_unmarked() {
+        let switched = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&switched);
+        let switch: LineErrorSwitch = Arc::new(move |on| log.lock().unwrap().push(on));
+        let (io, mut events) = PortIo::with_line_errors(
+            ScriptedLink(vec![Ok(b"a\xFF\x00\x81b\xFF\xFF".to_vec())]),
+            ScriptedLink(Vec::new()),
+            ScriptedLink(Vec::new()),
+            Some(switch),
+        );
+        assert!(io.reports_line_errors());
+        assert!(matches!(
+            futures::executor::block_on(events.next()).unwrap(),
+            PortEvent::LineErrors(1)
+        ));
+        match futures::executor::block_on(events.next()).unwrap() {
+            PortEvent::Received(data, _) => assert_eq!(data, b"a\x81b\xFF"),
+            other => panic!("expected Received, got {:?}", other),
+        }
+        let exclusive = io.exclusive();
+        drop(exclusive.lock());
+        drop(exclusive);
+        assert_eq!(*switched.lock().unwrap(), [false, true]);
+    }
+
+    #[test]
+    fn would_block_does_not_end_the_read_loop() {
+        let (io, mut events) = PortIo::new(
+            ScriptedLink(vec![
+                Err(io::ErrorKind::WouldBlock.into()),
+                Err(io::ErrorKind::WouldBlock.into()),
+                Ok(b"ok".to_vec()),
+            ]),
+            ScriptedLink(Vec::new()),
+            ScriptedLink(Vec::new()),
+        );
+        match futures::executor::block_on(events.next()).unwrap() {
+            PortEvent::Received(data, _) => assert_eq!(data, b"ok"),
+            other => panic!("expected Received, got {:?}", other),
+        }
+        // Joins the threads, which ends the stream once everything they sent is read
+        drop(io);
+        let rest: Vec<_> = futures::executor::block_on(events.collect());
+        assert!(!rest
+            .iter()
+            .any(|event| matches!(event, PortEvent::ReadFailed { .. })));
+    }
+}
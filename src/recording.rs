@@ -0,0 +1,51 @@
+use crate::hex;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Transmitted writes with the time each one left, counted from the start of the recording
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TxRecording {
+    pub chunks: Vec<RecordedChunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedChunk {
+    pub at_ms: u64,
+    // Payload as "AA BB" so the file stays readable
+    pub hex: String,
+}
+
+impl TxRecording {
+    pub fn push(&mut self, at: Duration, bytes: &[u8]) {
+        self.chunks.push(RecordedChunk {
+            at_ms: at.as_millis() as u64,
+            hex: hex::bytes_to_hex(bytes),
+        });
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.chunks.last().map_or(0, |chunk| chunk.at_ms))
+    }
+
+    // Pause between the previous chunk and `index`; the first chunk goes out at once
+    pub fn delay_before(&self, index: usize, speed: f64) -> Duration {
+        let gap = match index {
+            0 => 0,
+            _ => self.chunks[index]
+                .at_ms
+                .saturating_sub(self.chunks[index - 1].at_ms),
+        };
+        Duration::from_secs_f64(gap as f64 / 1000.0 / speed)
+    }
+
+    // Checks every chunk up front so a bad file fails before anything is sent
+    pub fn decode(&self) -> Result<Vec<Vec<u8>>, String> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                hex::hex_to_bytes(&chunk.hex).map_err(|e| format!("фрагмент {}: {}", i + 1, e))
+            })
+            .collect()
+    }
+}
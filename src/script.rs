@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Send(String),
+    Expect { pattern: String, timeout: Duration },
+}
+
+pub fn parse(source: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let step = match keyword.to_ascii_uppercase().as_str() {
+            "SEND" => Step::Send(rest.trim().to_string()),
+            "EXPECT" => {
+                let (pattern, timeout) = rest
+                    .trim()
+                    .rsplit_once(' ')
+                    .ok_or_else(|| format!("строка {}: EXPECT <текст> <мс>", number + 1))?;
+                let timeout = timeout.parse::<u64>().map_err(|_| {
+                    format!("строка {}: '{}' не является таймаутом", number + 1, timeout)
+                })?;
+                Step::Expect {
+                    pattern: pattern.trim().to_string(),
+                    timeout: Duration::from_millis(timeout),
+                }
+            }
+            other => {
+                return Err(format!(
+                    "строка {}: неизвестная команда '{}'",
+                    number + 1,
+                    other
+                ))
+            }
+        };
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let steps = parse("# login\n\n  send user\n   \n  # then wait\nEXPECT OK 500\n").unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                Step::Send("user".to_string()),
+                Step::Expect {
+                    pattern: "OK".to_string(),
+                    timeout: Duration::from_millis(500),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn expect_pattern_keeps_its_spaces() {
+        // Only the last word is the timeout
+        assert_eq!(
+            parse("EXPECT login ok: ready  2000").unwrap(),
+            vec![Step::Expect {
+                pattern: "login ok: ready".to_string(),
+                timeout: Duration::from_millis(2000),
+            }]
+        );
+    }
+
+    #[test]
+    fn bad_lines_are_reported_with_their_number() {
+        let error = parse("SEND AT\nEXPECT OK").unwrap_err();
+        assert!(error.starts_with("строка 2:"), "{}", error);
+        assert!(parse("EXPECT").is_err());
+        let error = parse("SEND AT\n\nEXPECT OK soon").unwrap_err();
+        assert!(error.starts_with("строка 3:"), "{}", error);
+        assert!(error.contains("'soon'"), "{}", error);
+        let error = parse("wait 100").unwrap_err();
+        assert!(error.contains("'WAIT'"), "{}", error);
+    }
+}
@@ -1,21 +1,734 @@
-use serialport::SerialPortInfo;
+use serialport::{
+    DataBits, FlowControl, Parity, SerialPort, SerialPortInfo, SerialPortType, StopBits,
+};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio_serial::{SerialPortBuilderExt, SerialStream};
+use std::time::{Duration, Instant};
 
-pub async fn list_ports() -> Vec<String> {
-    match serialport::available_ports() {
-        Ok(ports) => ports.into_iter().map(|p| p.port_name).collect(),
-        Err(_) => vec![],
+// serialport itself only drives None/Even/Odd. Mark and Space are "sticky" parity: the
+// port is opened without parity and the parity bit is then pinned where the OS allows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParityOption {
+    None,
+    Even,
+    Odd,
+    Mark,
+    Space,
+}
+
+impl ParityOption {
+    // What the builder is given; mark and space are set on the open port
+    pub fn base_parity(self) -> Parity {
+        match self {
+            ParityOption::Even => Parity::Even,
+            ParityOption::Odd => Parity::Odd,
+            ParityOption::None | ParityOption::Mark | ParityOption::Space => Parity::None,
+        }
+    }
+}
+
+impl std::fmt::Display for ParityOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            ParityOption::None => "None",
+            ParityOption::Even => "Even",
+            ParityOption::Odd => "Odd",
+            ParityOption::Mark => "Mark",
+            ParityOption::Space => "Space",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PortSettings {
+    pub port_name: Option<String>,
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: ParityOption,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    // Lock the device against other programs while it is open
+    pub exclusive: bool,
+    // Passive tap: the port is only read, every write path is refused
+    pub read_only: bool,
+    pub connected: bool,
+}
+
+impl Default for PortSettings {
+    fn default() -> Self {
+        Self {
+            port_name: None,
+            baud_rate: 9600,
+            data_bits: DataBits::Eight,
+            parity: ParityOption::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            exclusive: true,
+            read_only: false,
+            connected: false,
+        }
+    }
+}
+
+impl PortSettings {
+    // Short form like "8N1"
+    pub fn framing(&self) -> String {
+        framing_label(self.data_bits, self.parity, self.stop_bits)
+    }
+
+    // Bits on the wire per byte: start bit, data bits, parity, stop bits (10 for 8N1)
+    pub fn bits_per_byte(&self) -> u8 {
+        let data = match self.data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        };
+        let parity = if self.parity == ParityOption::None {
+            0
+        } else {
+            1
+        };
+        let stop = match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        1 + data + parity + stop
+    }
+}
+
+pub fn framing_label(data_bits: DataBits, parity: ParityOption, stop_bits: StopBits) -> String {
+    let parity = match parity {
+        ParityOption::None => 'N',
+        ParityOption::Even => 'E',
+        ParityOption::Odd => 'O',
+        ParityOption::Mark => 'M',
+        ParityOption::Space => 'S',
+    };
+    format!("{}{}{}", data_bits, parity, stop_bits)
+}
+
+pub const FRAMING_PRESETS: [(DataBits, ParityOption, StopBits); 5] = [
+    (DataBits::Eight, ParityOption::None, StopBits::One),
+    (DataBits::Eight, ParityOption::Even, StopBits::One),
+    (DataBits::Eight, ParityOption::Odd, StopBits::One),
+    (DataBits::Seven, ParityOption::Even, StopBits::One),
+    (DataBits::Seven, ParityOption::Odd, StopBits::One),
+];
+pub const DATA_BITS: [DataBits; 4] = [
+    DataBits::Five,
+    DataBits::Six,
+    DataBits::Seven,
+    DataBits::Eight,
+];
+pub const PARITIES: [ParityOption; 5] = [
+    ParityOption::None,
+    ParityOption::Even,
+    ParityOption::Odd,
+    ParityOption::Mark,
+    ParityOption::Space,
+];
+pub const STOP_BITS: [StopBits; 2] = [StopBits::One, StopBits::Two];
+pub const FLOW_CONTROLS: [FlowControl; 3] = [
+    FlowControl::None,
+    FlowControl::Software,
+    FlowControl::Hardware,
+];
+
+pub const SCAN_BAUD_RATES: [u32; 11] = [
+    1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600,
+];
+// How long each rate listens; the device has to be talking on its own
+pub const BAUD_SCAN_WINDOW: Duration = Duration::from_millis(600);
+// Fewer bytes than this say nothing about the rate
+pub const BAUD_SCAN_MIN_BYTES: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BaudScore {
+    pub bytes: usize,
+    // Share of the capture that decodes as text, 0.0..=1.0
+    pub text: f32,
+}
+
+// Results of a baud-rate scan, filled in one rate at a time
+#[derive(Debug, Clone, Default)]
+pub struct BaudScan {
+    pub results: Vec<(u32, Result<BaudScore, String>)>,
+    pub running: bool,
+}
+
+impl BaudScan {
+    // The rate with the most text-like output; ties go to the one that received more
+    pub fn best(&self) -> Option<u32> {
+        self.results
+            .iter()
+            .filter_map(|(rate, result)| result.as_ref().ok().map(|score| (*rate, score)))
+            .filter(|(_, score)| score.bytes >= BAUD_SCAN_MIN_BYTES)
+            .max_by(|(_, a), (_, b)| a.text.total_cmp(&b.text).then(a.bytes.cmp(&b.bytes)))
+            .map(|(rate, _)| rate)
+    }
+}
+
+// Inverse of `framing_label`
+pub fn parse_framing(label: &str) -> Option<(DataBits, ParityOption, StopBits)> {
+    DATA_BITS.iter().find_map(|&data_bits| {
+        PARITIES.iter().find_map(|&parity| {
+            STOP_BITS
+                .iter()
+                .map(|&stop_bits| (data_bits, parity, stop_bits))
+                .find(|&(d, p, s)| framing_label(d, p, s) == label)
+        })
+    })
+}
+// Every field the OS reports for each port, one block per port
+pub fn port_report(ports: &[SerialPortInfo]) -> String {
+    let mut report = format!("Портов в системе: {}\n", ports.len());
+    for info in ports {
+        report.push_str(&format!("{}\n", info.port_name));
+        match &info.port_type {
+            SerialPortType::UsbPort(usb) => {
+                report.push_str(&format!(
+                    "  тип: USB\n  VID:PID: {:04X}:{:04X}\n",
+                    usb.vid, usb.pid
+                ));
+                let fields = [
+                    ("серийный номер", &usb.serial_number),
+                    ("производитель", &usb.manufacturer),
+                    ("продукт", &usb.product),
+                ];
+                for (name, value) in fields {
+                    report.push_str(&format!(
+                        "  {}: {}\n",
+                        name,
+                        value.as_deref().unwrap_or("—")
+                    ));
+                }
+            }
+            SerialPortType::PciPort => report.push_str("  тип: PCI\n"),
+            SerialPortType::BluetoothPort => report.push_str("  тип: Bluetooth\n"),
+            // The OS gave nothing beyond the name
+            SerialPortType::Unknown => report.push_str("  тип: неизвестен\n"),
+        }
+    }
+    report
+}
+
+// "/dev/ttyUSB0" -> "ttyUSB0"; Windows names are already short
+pub fn port_label(port: &str) -> &str {
+    port.rsplit('/').next().unwrap_or(port)
+}
+pub fn open_error_hint(e: &serialport::Error) -> Option<&'static str> {
+    let busy = e.description.to_lowercase().contains("busy");
+    match e.kind() {
+        // Windows reports a port held by another program as access denied
+        serialport::ErrorKind::Io(io::ErrorKind::PermissionDenied) if cfg!(windows) => Some(
+            "Порт занят другой программой (терминал, IDE, загрузчик) — закройте ее и повторите",
+        ),
+        serialport::ErrorKind::Io(io::ErrorKind::PermissionDenied) => Some(
+            "Нет прав на порт: добавьте пользователя в группу dialout \
+             (sudo usermod -aG dialout $USER) и перезайдите в систему",
+        ),
+        serialport::ErrorKind::NoDevice if busy => Some(
+            "Порт занят: возможно, его держит другая программа (minicom, screen, ModemManager)",
+        ),
+        serialport::ErrorKind::Io(io::ErrorKind::NotFound) => {
+            Some("Порт не найден: устройство отключено или переименовано, обновите список")
+        }
+        _ => None,
+    }
+}
+
+pub const RAW_CHUNK_SIZE: usize = 256;
+
+// Writes the data as is in small chunks so progress and cancellation stay responsive
+pub fn send_raw(
+    port: &mut dyn SerialPort,
+    data: &[u8],
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize),
+    mut flow: Option<&mut dyn FnMut(FlowEvent)>,
+) -> Result<(), String> {
+    let mut sent = 0;
+    let mut paused = false;
+    for chunk in data.chunks(RAW_CHUNK_SIZE) {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("отменено".to_string());
+        }
+        if let Some(flow) = flow.as_deref_mut() {
+            wait_for_xon(port, cancel, &mut paused, flow)?;
+        }
+        port.write_all(chunk).map_err(|e| e.to_string())?;
+        sent += chunk.len();
+        progress(sent);
+    }
+    port.flush().map_err(|e| e.to_string())
+}
+
+pub const XON: u8 = 0x11;
+pub const XOFF: u8 = 0x13;
+pub const XOFF_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+pub enum FlowEvent {
+    Paused(bool),
+    // Ordinary input that arrived while the transfer owned the port
+    Received(Vec<u8>),
+}
+
+// Consumes pending input before each chunk and blocks while the device holds XOFF.
+// Drivers that handle XON/XOFF themselves swallow the bytes, so this never triggers there.
+pub fn wait_for_xon(
+    port: &mut dyn SerialPort,
+    cancel: &AtomicBool,
+    paused: &mut bool,
+    flow: &mut dyn FnMut(FlowEvent),
+) -> Result<(), String> {
+    loop {
+        let queued = port.bytes_to_read().map_err(|e| e.to_string())? as usize;
+        if queued > 0 {
+            let mut buffer = vec![0; queued];
+            let n = match port.read(&mut buffer) {
+                Ok(n) => n,
+                Err(e) if is_transient_read_error(&e) => 0,
+                Err(e) => return Err(e.to_string()),
+            };
+            let was_paused = *paused;
+            let mut data = Vec::with_capacity(n);
+            for &b in &buffer[..n] {
+                match b {
+                    XOFF => *paused = true,
+                    XON => *paused = false,
+                    _ => data.push(b),
+                }
+            }
+            if !data.is_empty() {
+                flow(FlowEvent::Received(data));
+            }
+            if *paused != was_paused {
+                flow(FlowEvent::Paused(*paused));
+            }
+        }
+        if !*paused {
+            return Ok(());
+        }
+        if cancel.load(Ordering::Relaxed) {
+            return Err("отменено".to_string());
+        }
+        std::thread::sleep(XOFF_POLL_INTERVAL);
+    }
+}
+
+// Time on the wire for `bytes` bytes, each framed as `bits_per_byte` bits (10 for 8N1,
+// 11 for 8E1); ignores gaps between bytes, so real transfers only take longer
+pub fn estimate_transfer_secs(bytes: usize, baud: u32, bits_per_byte: u8) -> f64 {
+    bytes as f64 * bits_per_byte as f64 / baud.max(1) as f64
+}
+
+// How long a reader backs off after WouldBlock, which unlike a timeout returns at once
+pub const WOULD_BLOCK_BACKOFF: Duration = Duration::from_millis(5);
+
+// Read errors that only mean "no data yet": TimedOut from a blocking read, WouldBlock from
+// drivers on some platforms that behave as non-blocking
+pub fn is_transient_read_error(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+    )
+}
+
+// Write errors after which the handle is useless: the device was unplugged or the driver
+// dropped it. Timeouts and the like leave the port usable and are not counted
+pub fn is_connection_lost_error(e: &io::Error) -> bool {
+    if matches!(
+        e.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::UnexpectedEof
+    ) {
+        return true;
+    }
+    // serialport hands EIO, ENXIO and ENODEV from read/write, and the POLLERR of its poll,
+    // over as a bare Other without the OS code; only the errno text is left of them
+    #[cfg(unix)]
+    const LOST_MESSAGES: &[&str] = &["I/O error", "No such device or address", "No such device"];
+    #[cfg(unix)]
+    if e.kind() == io::ErrorKind::Other
+        && e.raw_os_error().is_none()
+        && LOST_MESSAGES.contains(&e.to_string().as_str())
+    {
+        return true;
     }
+    // EIO, ENXIO, ENODEV
+    #[cfg(unix)]
+    const LOST_CODES: &[i32] = &[5, 6, 19];
+    // ERROR_ACCESS_DENIED, ERROR_BAD_COMMAND, ERROR_GEN_FAILURE, ERROR_OPERATION_ABORTED,
+    // ERROR_DEVICE_NOT_CONNECTED
+    #[cfg(windows)]
+    const LOST_CODES: &[i32] = &[5, 22, 31, 995, 1167];
+    #[cfg(not(any(unix, windows)))]
+    const LOST_CODES: &[i32] = &[];
+    e.raw_os_error()
+        .is_some_and(|code| LOST_CODES.contains(&code))
 }
 
-pub async fn open_port_async(
-    port_name: &str,
-    baud: u32,
-) -> Result<Arc<Mutex<SerialStream>>, String> {
-    match tokio_serial::new(port_name, baud).open_native_async() {
-        Ok(s) => Ok(Arc::new(Mutex::new(s))),
-        Err(e) => Err(e.to_string()),
+// Framing errors within FRAMING_SPIKE_WINDOW that count as a misconfiguration hint
+pub const FRAMING_SPIKE_COUNT: u64 = 5;
+pub const FRAMING_SPIKE_WINDOW: Duration = Duration::from_secs(10);
+
+// read() never reports a framing or parity error itself: the tty drops such a byte or reads
+// it as NUL. With PARMRK set it arrives as FF 00 <byte> instead (a break as FF 00 00), and
+// a real FF as FF FF. Marks may be split across reads, so the state carries over
+#[derive(Debug, Default)]
+pub struct LineErrorDecoder {
+    // Bytes of a mark seen so far: 0, 1 after FF, 2 after FF 00
+    marked: u8,
+}
+
+impl LineErrorDecoder {
+    // Appends the received bytes to `out`, garbled ones included, and returns how many
+    // of them were flagged
+    pub fn decode(&mut self, input: &[u8], out: &mut Vec<u8>) -> usize {
+        let mut errors = 0;
+        for &b in input {
+            self.marked = match (self.marked, b) {
+                (0, 0xFF) => 1,
+                (0, _) => {
+                    out.push(b);
+                    0
+                }
+                (1, 0x00) => 2,
+                (1, _) => {
+                    // FF FF is an escaped FF; anything else is not a mark after all
+                    out.push(0xFF);
+                    if b != 0xFF {
+                        out.push(b);
+                    }
+                    0
+                }
+                _ => {
+                    out.push(b);
+                    errors += 1;
+                    0
+                }
+            };
+        }
+        errors
+    }
+}
+
+// Turns the tty's error marks on and off; any handle of the port will do
+pub type LineErrorSwitch = Arc<dyn Fn(bool) + Send + Sync>;
+
+// On: mark bad bytes (PARMRK) and keep the ones IGNPAR would drop. Off: back to what
+// serialport sets up, where IGNPAR goes with parity checking being off
+#[cfg(unix)]
+fn set_error_marks(fd: std::os::unix::io::RawFd, on: bool) -> io::Result<()> {
+    // SAFETY: the caller keeps `fd` open, and termios is plain data
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if on {
+            termios.c_iflag |= libc::PARMRK;
+            termios.c_iflag &= !libc::IGNPAR;
+        } else {
+            termios.c_iflag &= !libc::PARMRK;
+            if termios.c_iflag & libc::INPCK == 0 {
+                termios.c_iflag |= libc::IGNPAR;
+            }
+        }
+        if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+// Collects whatever arrives within `window`; other read errors just end the capture early
+pub fn listen_for<R: Read + ?Sized>(port: &mut R, window: Duration) -> Vec<u8> {
+    let started = Instant::now();
+    let mut received = Vec::new();
+    let mut buffer = [0; 1024];
+    while started.elapsed() < window {
+        match port.read(&mut buffer) {
+            Ok(n) => received.extend_from_slice(&buffer[..n]),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(WOULD_BLOCK_BACKOFF)
+            }
+            Err(e) if is_transient_read_error(&e) => {}
+            Err(_) => break,
+        }
+    }
+    received
+}
+
+// Share of the bytes that decode to printable text or ordinary whitespace; a wrong rate
+// turns the stream into control codes and invalid UTF-8
+pub fn text_score(bytes: &[u8]) -> f32 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+    let good: usize = String::from_utf8_lossy(bytes)
+        .chars()
+        .filter(|&c| {
+            c != char::REPLACEMENT_CHARACTER && (!c.is_control() || matches!(c, '\t' | '\r' | '\n'))
+        })
+        .map(char::len_utf8)
+        .sum();
+    good as f32 / bytes.len() as f32
+}
+
+// Unix ports can be opened shared (TIOCEXCL off); Windows always locks COM ports.
+// `parity` replaces whatever parity the builder was given
+#[cfg(unix)]
+pub fn open_with_exclusivity(
+    builder: serialport::SerialPortBuilder,
+    parity: ParityOption,
+    exclusive: bool,
+) -> Result<Box<dyn SerialPort>, serialport::Error> {
+    Ok(Box::new(open_native(builder, parity, exclusive)?))
+}
+
+#[cfg(unix)]
+fn open_native(
+    builder: serialport::SerialPortBuilder,
+    parity: ParityOption,
+    exclusive: bool,
+) -> Result<serialport::TTYPort, serialport::Error> {
+    let mut port = builder.parity(parity.base_parity()).open_native()?;
+    port.set_exclusive(exclusive)?;
+    set_sticky_parity(&port, parity)?;
+    Ok(port)
+}
+
+// Like open_with_exclusivity, with framing and parity errors marked in the byte stream where
+// the OS can do that. The switch is tied to this handle and must not outlive it
+#[cfg(unix)]
+pub fn open_with_line_errors(
+    builder: serialport::SerialPortBuilder,
+    parity: ParityOption,
+    exclusive: bool,
+) -> Result<(Box<dyn SerialPort>, Option<LineErrorSwitch>), serialport::Error> {
+    use std::os::unix::io::AsRawFd;
+    let port = open_native(builder, parity, exclusive)?;
+    let fd = port.as_raw_fd();
+    // Without marks the port still works, only the error count is not available
+    let switch = set_error_marks(fd, true).ok().map(|()| {
+        Arc::new(move |on| {
+            let _ = set_error_marks(fd, on);
+        }) as LineErrorSwitch
+    });
+    Ok((Box::new(port), switch))
+}
+
+// Windows reports line errors only through ClearCommError, which serialport does not expose
+#[cfg(not(unix))]
+pub fn open_with_line_errors(
+    builder: serialport::SerialPortBuilder,
+    parity: ParityOption,
+    exclusive: bool,
+) -> Result<(Box<dyn SerialPort>, Option<LineErrorSwitch>), serialport::Error> {
+    Ok((open_with_exclusivity(builder, parity, exclusive)?, None))
+}
+
+#[cfg(not(unix))]
+pub fn open_with_exclusivity(
+    builder: serialport::SerialPortBuilder,
+    parity: ParityOption,
+    exclusive: bool,
+) -> Result<Box<dyn SerialPort>, serialport::Error> {
+    if !exclusive {
+        return Err(serialport::Error::new(
+            serialport::ErrorKind::InvalidInput,
+            "совместный доступ к COM-порту не поддерживается в Windows",
+        ));
+    }
+    refuse_sticky_parity(parity)?;
+    builder.parity(parity.base_parity()).open()
+}
+
+// Mark and space through CMSPAR, which only Linux termios has
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_sticky_parity(port: &serialport::TTYPort, parity: ParityOption) -> serialport::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    let mark = match parity {
+        ParityOption::Mark => true,
+        ParityOption::Space => false,
+        ParityOption::None | ParityOption::Even | ParityOption::Odd => return Ok(()),
+    };
+    let fd = port.as_raw_fd();
+    // SAFETY: `fd` is open for as long as `port` is borrowed, and termios is plain data
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        termios.c_cflag |= libc::PARENB | libc::CMSPAR;
+        if mark {
+            termios.c_cflag |= libc::PARODD;
+        } else {
+            termios.c_cflag &= !libc::PARODD;
+        }
+        if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn set_sticky_parity(_: &serialport::TTYPort, parity: ParityOption) -> serialport::Result<()> {
+    refuse_sticky_parity(parity)
+}
+
+// Opening with plain "no parity" instead would garble every byte without a word of warning
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn refuse_sticky_parity(parity: ParityOption) -> serialport::Result<()> {
+    match parity {
+        ParityOption::Mark | ParityOption::Space => Err(serialport::Error::new(
+            serialport::ErrorKind::InvalidInput,
+            format!("чётность {} не поддерживается на этой системе", parity),
+        )),
+        ParityOption::None | ParityOption::Even | ParityOption::Odd => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hands out scripted read results, then times out like an idle port
+    struct ScriptedReader(Vec<io::Result<Vec<u8>>>);
+
+    impl Read for ScriptedReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            let data = self.0.remove(0)?;
+            buf[..data.len()].copy_from_slice(&data);
+            Ok(data.len())
+        }
+    }
+
+    #[test]
+    fn transfer_estimates_match_hand_computed_values() {
+        // 1000 bytes of 8N1 at 9600: 10 000 bits / 9600 bit/s
+        assert!((estimate_transfer_secs(1000, 9600, 10) - 1.041_666).abs() < 1e-5);
+        // 8E1 adds a parity bit: 11 520 bytes * 11 bits / 115 200 bit/s = 1.1 s
+        assert!((estimate_transfer_secs(11_520, 115_200, 11) - 1.1).abs() < 1e-9);
+        assert_eq!(estimate_transfer_secs(0, 9600, 10), 0.0);
+        // A zero rate must not divide by zero
+        assert!(estimate_transfer_secs(10, 0, 10).is_finite());
+    }
+
+    #[test]
+    fn mark_and_space_round_trip_and_open_without_builder_parity() {
+        for framing in [
+            (DataBits::Eight, ParityOption::Mark, StopBits::One),
+            (DataBits::Seven, ParityOption::Space, StopBits::Two),
+        ] {
+            let (data_bits, parity, stop_bits) = framing;
+            let label = framing_label(data_bits, parity, stop_bits);
+            assert_eq!(parse_framing(&label), Some(framing));
+        }
+        assert_eq!(ParityOption::Odd.base_parity(), Parity::Odd);
+        assert_eq!(ParityOption::Mark.base_parity(), Parity::None);
+        let settings = PortSettings {
+            parity: ParityOption::Space,
+            ..PortSettings::default()
+        };
+        assert_eq!(settings.bits_per_byte(), 11);
+    }
+
+    #[test]
+    fn only_unplug_like_write_errors_count_as_lost() {
+        assert!(is_connection_lost_error(&io::ErrorKind::BrokenPipe.into()));
+        assert!(is_connection_lost_error(
+            &io::ErrorKind::NotConnected.into()
+        ));
+        #[cfg(unix)]
+        assert!(is_connection_lost_error(&io::Error::from_raw_os_error(5)));
+        // serialport's EIO, which has lost its OS code on the way
+        #[cfg(unix)]
+        assert!(is_connection_lost_error(&io::Error::other("I/O error")));
+        assert!(!is_connection_lost_error(&io::Error::other("flush failed")));
+        #[cfg(windows)]
+        assert!(is_connection_lost_error(&io::Error::from_raw_os_error(
+            1167
+        )));
+        assert!(!is_connection_lost_error(&io::ErrorKind::TimedOut.into()));
+        assert!(!is_connection_lost_error(
+            &io::ErrorKind::InvalidInput.into()
+        ));
+    }
+
+    #[test]
+    fn error_marks_are_counted_and_stripped() {
+        let mut decoder = LineErrorDecoder::default();
+        let mut out = Vec::new();
+        // A garbled 'x', an escaped FF, a break, then plain text
+        let errors = decoder.decode(b"a\xFF\x00x\xFF\xFFb\xFF\x00\x00ok", &mut out);
+        assert_eq!(errors, 2);
+        assert_eq!(out, b"ax\xFFb\x00ok");
+    }
+
+    #[test]
+    fn error_marks_split_across_reads_are_joined() {
+        let mut decoder = LineErrorDecoder::default();
+        let mut out = Vec::new();
+        assert_eq!(decoder.decode(b"a\xFF", &mut out), 0);
+        assert_eq!(decoder.decode(b"\x00", &mut out), 0);
+        assert_eq!(decoder.decode(b"zb\xFF", &mut out), 1);
+        assert_eq!(decoder.decode(b"\xFF", &mut out), 0);
+        assert_eq!(out, b"azb\xFF");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn error_marks_switch_parmrk_and_ignpar() {
+        use std::os::unix::io::AsRawFd;
+        let (_master, slave) = serialport::TTYPort::pair().unwrap();
+        let fd = slave.as_raw_fd();
+        let iflag = || {
+            // SAFETY: `slave` keeps `fd` open for the whole test
+            unsafe {
+                let mut termios: libc::termios = std::mem::zeroed();
+                assert_eq!(libc::tcgetattr(fd, &mut termios), 0);
+                termios.c_iflag
+            }
+        };
+        set_error_marks(fd, true).unwrap();
+        assert_ne!(iflag() & libc::PARMRK, 0);
+        assert_eq!(iflag() & libc::IGNPAR, 0);
+        // No parity checking on the pair, so IGNPAR comes back as serialport sets it
+        set_error_marks(fd, false).unwrap();
+        assert_eq!(iflag() & libc::PARMRK, 0);
+        assert_ne!(iflag() & libc::IGNPAR, 0);
+    }
+
+    #[test]
+    fn would_block_does_not_end_listening() {
+        let mut reader = ScriptedReader(vec![
+            Ok(b"ab".to_vec()),
+            Err(io::ErrorKind::WouldBlock.into()),
+            Err(io::ErrorKind::WouldBlock.into()),
+            Ok(b"cd".to_vec()),
+        ]);
+        assert_eq!(listen_for(&mut reader, Duration::from_millis(50)), b"abcd");
+    }
+
+    #[test]
+    fn other_errors_end_listening() {
+        let mut reader = ScriptedReader(vec![
+            Ok(b"ab".to_vec()),
+            Err(io::ErrorKind::BrokenPipe.into()),
+            Ok(b"cd".to_vec()),
+        ]);
+        assert_eq!(listen_for(&mut reader, Duration::from_millis(50)), b"ab");
     }
 }
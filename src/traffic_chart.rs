@@ -0,0 +1,108 @@
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+pub const WIDTH: u32 = 800;
+pub const HEIGHT: u32 = 300;
+
+// Samples are (rx, tx) bytes per second, oldest first
+pub fn draw<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    samples: &[(u64, u64)],
+) -> Result<(), String> {
+    root.fill(&WHITE).map_err(|e| e.to_string())?;
+    let max = samples
+        .iter()
+        .map(|&(rx, tx)| rx.max(tx))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut chart = ChartBuilder::on(root)
+        .caption("Трафик, байт/с", ("sans-serif", 16))
+        .margin(10)
+        .x_label_area_size(25)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0..samples.len().max(1), 0..max)
+        .map_err(|e| e.to_string())?;
+    chart
+        .configure_mesh()
+        .x_desc("с")
+        .draw()
+        .map_err(|e| e.to_string())?;
+    let rx: Vec<u64> = samples.iter().map(|s| s.0).collect();
+    let tx: Vec<u64> = samples.iter().map(|s| s.1).collect();
+    for (label, color, values) in [("RX", GREEN, rx), ("TX", BLUE, tx)] {
+        chart
+            .draw_series(LineSeries::new(values.into_iter().enumerate(), &color))
+            .map_err(|e| e.to_string())?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(|e| e.to_string())?;
+    root.present().map_err(|e| e.to_string())
+}
+
+pub fn to_svg(samples: &[(u64, u64)]) -> Result<String, String> {
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (WIDTH, HEIGHT)).into_drawing_area();
+        draw(&root, samples)?;
+    }
+    Ok(svg)
+}
+
+pub const SPARKLINE_WIDTH: u32 = 120;
+pub const SPARKLINE_HEIGHT: u32 = 24;
+
+// Bare trend line for the status bar: no axes, scaled to the samples' own range
+pub fn sparkline_svg(values: &[f64]) -> Result<String, String> {
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (SPARKLINE_WIDTH, SPARKLINE_HEIGHT))
+            .into_drawing_area();
+        let (min, max) = values
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        // A flat series still needs a non-empty range to draw
+        let (min, max) = if min < max {
+            (min, max)
+        } else {
+            (min - 1.0, min + 1.0)
+        };
+        let mut chart = ChartBuilder::on(&root)
+            .margin(2)
+            .build_cartesian_2d(0..values.len().max(2) - 1, min..max)
+            .map_err(|e| e.to_string())?;
+        chart
+            .draw_series(LineSeries::new(
+                values.iter().copied().enumerate(),
+                GREEN.stroke_width(2),
+            ))
+            .map_err(|e| e.to_string())?;
+        root.present().map_err(|e| e.to_string())?;
+    }
+    Ok(svg)
+}
+
+// The format follows the extension: ".svg" writes SVG, anything else PNG
+pub fn save(path: &Path, samples: &[(u64, u64)]) -> Result<(), String> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        draw(
+            &SVGBackend::new(path, (WIDTH, HEIGHT)).into_drawing_area(),
+            samples,
+        )
+    } else {
+        draw(
+            &BitMapBackend::new(path, (WIDTH, HEIGHT)).into_drawing_area(),
+            samples,
+        )
+    }
+}
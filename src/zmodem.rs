@@ -0,0 +1,418 @@
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const ZPAD: u8 = b'*';
+const ZDLE: u8 = 0x18;
+const ZHEX: u8 = b'B';
+const ZBIN32: u8 = b'C';
+const XON: u8 = 0x11;
+
+pub const ZRQINIT: u8 = 0;
+pub const ZRINIT: u8 = 1;
+pub const ZACK: u8 = 3;
+pub const ZFILE: u8 = 4;
+pub const ZSKIP: u8 = 5;
+pub const ZNAK: u8 = 6;
+pub const ZABORT: u8 = 7;
+pub const ZFIN: u8 = 8;
+pub const ZRPOS: u8 = 9;
+pub const ZDATA: u8 = 10;
+pub const ZEOF: u8 = 11;
+pub const ZFERR: u8 = 12;
+
+// Subpacket terminators
+pub const ZCRCE: u8 = b'h';
+pub const ZCRCG: u8 = b'i';
+pub const ZCRCW: u8 = b'k';
+
+// ZF0 flag in a ZFILE header: binary transfer
+const ZCBIN: u8 = 1;
+
+// Eight CANs abort the session on the receiver, the backspaces erase them from a shell
+const ABORT_SEQUENCE: [u8; 18] = [
+    0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x08,
+    0x08, 0x08,
+];
+const CANCELLED: &str = "передача отменена";
+
+const SUBPACKET_LEN: usize = 1024;
+const HEADER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub frame_type: u8,
+    pub position: u32,
+}
+
+// CRC-16/XMODEM used by hex headers
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// Raw CRC-32 (IEEE) register update; start from 0xFFFF_FFFF and invert at the end
+pub fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(0xFFFF_FFFF, data)
+}
+
+// ZDLE-escapes the bytes that would confuse the link: ZDLE itself and DLE/XON/XOFF
+fn push_escaped(out: &mut Vec<u8>, byte: u8) {
+    match byte {
+        ZDLE | 0x10 | 0x90 | 0x11 | 0x91 | 0x13 | 0x93 => {
+            out.push(ZDLE);
+            out.push(byte ^ 0x40);
+        }
+        _ => out.push(byte),
+    }
+}
+
+pub fn hex_header(frame_type: u8, position: u32) -> Vec<u8> {
+    let mut body = vec![frame_type];
+    body.extend_from_slice(&position.to_le_bytes());
+    let crc = crc16(&body);
+
+    let mut out = vec![ZPAD, ZPAD, ZDLE, ZHEX];
+    for byte in body.iter().chain(crc.to_be_bytes().iter()) {
+        out.extend_from_slice(format!("{:02x}", byte).as_bytes());
+    }
+    out.extend_from_slice(&[b'\r', 0x8A]);
+    if frame_type != ZACK && frame_type != ZFIN {
+        out.push(XON);
+    }
+    out
+}
+
+pub fn bin32_header(frame_type: u8, position: u32) -> Vec<u8> {
+    let mut body = vec![frame_type];
+    body.extend_from_slice(&position.to_le_bytes());
+    let crc = crc32(&body);
+
+    let mut out = vec![ZPAD, ZDLE, ZBIN32];
+    for &byte in body.iter().chain(crc.to_le_bytes().iter()) {
+        push_escaped(&mut out, byte);
+    }
+    out
+}
+
+// Data subpacket with a CRC-32 covering the payload and the terminator byte
+pub fn data_subpacket(data: &[u8], frame_end: u8) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 8 + 12);
+    for &byte in data {
+        push_escaped(&mut out, byte);
+    }
+    out.push(ZDLE);
+    out.push(frame_end);
+    let crc = !crc32_update(crc32_update(0xFFFF_FFFF, data), &[frame_end]);
+    for &byte in crc.to_le_bytes().iter() {
+        push_escaped(&mut out, byte);
+    }
+    out
+}
+
+// Parses the 14 hex digits that follow "ZDLE B" in a hex header
+pub fn parse_hex_header(digits: &[u8]) -> Option<Header> {
+    if digits.len() < 14 {
+        return None;
+    }
+    let mut bytes = [0u8; 7];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let pair = std::str::from_utf8(&digits[i * 2..i * 2 + 2]).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    if crc16(&bytes[..5]) != u16::from_be_bytes([bytes[5], bytes[6]]) {
+        return None;
+    }
+    Some(Header {
+        frame_type: bytes[0],
+        position: u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]),
+    })
+}
+
+// Waits for the next valid hex header; receivers such as `rz` answer with hex headers only
+fn read_header<P: Read + ?Sized>(
+    port: &mut P,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<Header, String> {
+    let deadline = Instant::now() + timeout;
+    let mut byte = [0u8; 1];
+    let mut after_zdle = false;
+    let mut digits: Option<Vec<u8>> = None;
+    let mut cancel_run = 0;
+
+    while Instant::now() < deadline {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(CANCELLED.into());
+        }
+        match port.read(&mut byte) {
+            Ok(0) => continue,
+            Ok(_) => {}
+            Err(e)
+                if e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock =>
+            {
+                continue
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+        let b = byte[0];
+
+        // Five CAN (== ZDLE) bytes in a row is the abort sequence
+        cancel_run = if b == ZDLE { cancel_run + 1 } else { 0 };
+        if cancel_run >= 5 {
+            return Err("передача отменена получателем".into());
+        }
+
+        if let Some(collected) = digits.as_mut() {
+            collected.push(b);
+            if collected.len() == 14 {
+                if let Some(header) = parse_hex_header(collected) {
+                    return Ok(header);
+                }
+                digits = None;
+            }
+            continue;
+        }
+        if after_zdle && b == ZHEX {
+            digits = Some(Vec::with_capacity(14));
+        }
+        after_zdle = b == ZDLE;
+    }
+    Err("таймаут ожидания ответа получателя".into())
+}
+
+fn write<P: Write + ?Sized>(port: &mut P, bytes: &[u8]) -> Result<(), String> {
+    port.write_all(bytes).map_err(|e| e.to_string())
+}
+
+// Sends one file and closes the session; `progress` receives the sent byte offset.
+// Setting `cancel` aborts the transfer and tells the receiver to give up too.
+pub fn send_file<P: Read + Write + ?Sized>(
+    port: &mut P,
+    file_name: &str,
+    data: &[u8],
+    cancel: &AtomicBool,
+    progress: impl FnMut(usize),
+) -> Result<(), String> {
+    let result = send_session(port, file_name, data, cancel, progress);
+    if result.is_err() && cancel.load(Ordering::Relaxed) {
+        let _ = port.write_all(&ABORT_SEQUENCE);
+        return Err(CANCELLED.into());
+    }
+    result
+}
+
+fn send_session<P: Read + Write + ?Sized>(
+    port: &mut P,
+    file_name: &str,
+    data: &[u8],
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize),
+) -> Result<(), String> {
+    // "rz\r" lets shells with auto-start launch the receiver
+    write(port, b"rz\r")?;
+    write(port, &hex_header(ZRQINIT, 0))?;
+    loop {
+        match read_header(port, HEADER_TIMEOUT, cancel)?.frame_type {
+            ZRINIT => break,
+            ZNAK => write(port, &hex_header(ZRQINIT, 0))?,
+            _ => {}
+        }
+    }
+
+    let file_info = format!("{}\0{} 0 0 0\0", file_name, data.len());
+    let mut offset = loop {
+        write(
+            port,
+            &bin32_header(ZFILE, u32::from_le_bytes([0, 0, 0, ZCBIN])),
+        )?;
+        write(port, &data_subpacket(file_info.as_bytes(), ZCRCW))?;
+        let header = read_header(port, HEADER_TIMEOUT, cancel)?;
+        match header.frame_type {
+            ZRPOS => break header.position as usize,
+            ZSKIP => return Err("получатель пропустил файл".into()),
+            ZABORT | ZFERR => return Err("получатель прервал передачу".into()),
+            _ => continue,
+        }
+    };
+
+    loop {
+        offset = offset.min(data.len());
+        if offset < data.len() {
+            write(port, &bin32_header(ZDATA, offset as u32))?;
+            while offset < data.len() {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(CANCELLED.into());
+                }
+                let end = (offset + SUBPACKET_LEN).min(data.len());
+                let frame_end = if end == data.len() { ZCRCE } else { ZCRCG };
+                write(port, &data_subpacket(&data[offset..end], frame_end))?;
+                offset = end;
+                progress(offset);
+            }
+        }
+        write(port, &bin32_header(ZEOF, data.len() as u32))?;
+
+        let header = read_header(port, HEADER_TIMEOUT, cancel)?;
+        match header.frame_type {
+            ZRINIT | ZSKIP => break,
+            // The receiver asks to resume from an earlier position
+            ZRPOS => offset = header.position as usize,
+            ZABORT | ZFERR => return Err("получатель прервал передачу".into()),
+            _ => {}
+        }
+    }
+
+    write(port, &hex_header(ZFIN, 0))?;
+    // The receiver's ZFIN is a courtesy; the file is already confirmed
+    let _ = read_header(port, HEADER_TIMEOUT, cancel);
+    write(port, b"OO")?;
+    progress(data.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn crc_check_values() {
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn zrqinit_hex_header_matches_reference() {
+        assert_eq!(
+            hex_header(ZRQINIT, 0),
+            b"**\x18B00000000000000\r\x8a\x11".to_vec()
+        );
+    }
+
+    #[test]
+    fn hex_header_round_trip() {
+        let encoded = hex_header(ZRPOS, 0x0001_2345);
+        let header = parse_hex_header(&encoded[4..18]).unwrap();
+        assert_eq!(
+            header,
+            Header {
+                frame_type: ZRPOS,
+                position: 0x0001_2345
+            }
+        );
+    }
+
+    #[test]
+    fn corrupted_hex_header_is_rejected() {
+        let mut encoded = hex_header(ZRINIT, 0);
+        encoded[6] = b'7';
+        assert!(parse_hex_header(&encoded[4..18]).is_none());
+    }
+
+    #[test]
+    fn subpacket_escapes_control_bytes() {
+        let packet = data_subpacket(&[0x41, ZDLE, 0x11], ZCRCE);
+        assert_eq!(&packet[..7], &[0x41, ZDLE, 0x58, ZDLE, 0x51, ZDLE, ZCRCE]);
+    }
+
+    #[test]
+    fn subpacket_crc_covers_terminator() {
+        let packet = data_subpacket(b"abc", ZCRCW);
+        let mut covered = b"abc".to_vec();
+        covered.push(ZCRCW);
+        assert_eq!(&packet[5..], &crc32(&covered).to_le_bytes());
+    }
+
+    // Receiver replies are queued up front; reads past them time out like a real port
+    struct ScriptedPort {
+        incoming: VecDeque<u8>,
+        written: Vec<u8>,
+    }
+
+    impl Read for ScriptedPort {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.incoming.pop_front() {
+                Some(b) => {
+                    buf[0] = b;
+                    Ok(1)
+                }
+                None => Err(io::ErrorKind::TimedOut.into()),
+            }
+        }
+    }
+
+    impl Write for ScriptedPort {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn send_file_runs_full_session() {
+        let mut incoming = Vec::new();
+        for (frame_type, position) in [(ZRINIT, 0), (ZRPOS, 0), (ZRINIT, 0), (ZFIN, 0)] {
+            incoming.extend(hex_header(frame_type, position));
+        }
+        let mut port = ScriptedPort {
+            incoming: incoming.into(),
+            written: Vec::new(),
+        };
+        let data = vec![0x5Au8; 2500];
+        let mut last_progress = 0;
+
+        send_file(
+            &mut port,
+            "fw.bin",
+            &data,
+            &AtomicBool::new(false),
+            |sent| last_progress = sent,
+        )
+        .unwrap();
+
+        assert_eq!(last_progress, data.len());
+        assert!(port.written.ends_with(b"OO"));
+        let fin = hex_header(ZFIN, 0);
+        assert!(port.written.windows(fin.len()).any(|w| w == fin.as_slice()));
+    }
+
+    #[test]
+    fn cancelled_transfer_sends_abort_sequence() {
+        let mut port = ScriptedPort {
+            incoming: VecDeque::new(),
+            written: Vec::new(),
+        };
+        let result = send_file(&mut port, "fw.bin", b"data", &AtomicBool::new(true), |_| {});
+
+        assert_eq!(result, Err(CANCELLED.to_string()));
+        assert!(port.written.ends_with(&ABORT_SEQUENCE));
+    }
+}